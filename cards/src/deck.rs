@@ -0,0 +1,197 @@
+// Deck is an ordered pile of cards built from one or more standard decks,
+// dealt from the top.
+
+use super::error::CardError;
+use super::rank::Rank;
+use super::suit::Suit;
+use super::{Card, DISTINCT_CARDS};
+
+/// Rng supplies the randomness for Deck::shuffle. Implementations must
+/// return a uniformly distributed value in 0..bound.
+pub trait Rng {
+    fn below(&mut self, bound: usize) -> usize;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deck {
+    cards: Vec<Card>,
+    next_card: usize,
+}
+
+impl Deck {
+    // new builds the given number of complete decks, in card order
+    pub fn new(decks: usize) -> Deck {
+        Deck::with_suits(decks, &super::suit::iter().collect::<Vec<Suit>>())
+    }
+
+    // with_suits builds copies of the thirteen cards of each given suit,
+    // as used by one and two suit games: Deck::with_suits(8, &[Spades])
+    pub fn with_suits(copies: usize, suits: &[Suit]) -> Deck {
+        let mut cards: Vec<Card> = Vec::new();
+        for _ in 0..copies {
+            for card in Card::iter() {
+                if suits.contains(&card.suit) {
+                    cards.push(card);
+                }
+            }
+        }
+        Deck::from_cards(cards)
+    }
+
+    // from_cards takes the cards in deal order, first card on top
+    pub fn from_cards(cards: Vec<Card>) -> Deck {
+        Deck {
+            cards,
+            next_card: 0,
+        }
+    }
+
+    // shuffle puts the undealt cards in random order with a Fisher-Yates
+    // shuffle, taking one value from the rng for each card from the bottom up
+    pub fn shuffle<R: Rng>(&mut self, rng: &mut R) {
+        let undealt = &mut self.cards[self.next_card..];
+        for i in (1..undealt.len()).rev() {
+            let j = rng.below(i + 1);
+            undealt.swap(i, j);
+        }
+    }
+
+    pub fn deal(&mut self) -> Option<Card> {
+        let card = self.cards.get(self.next_card).copied();
+        if card.is_some() {
+            self.next_card += 1;
+        }
+        card
+    }
+
+    // remaining lists the undealt cards, top card first
+    pub fn remaining(&self) -> &[Card] {
+        &self.cards[self.next_card..]
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards.len() - self.next_card
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn count(&self, card: Card) -> usize {
+        self.remaining().iter().filter(|c| **c == card).count()
+    }
+
+    pub fn count_suit(&self, suit: Suit) -> usize {
+        self.remaining().iter().filter(|c| c.suit == suit).count()
+    }
+
+    pub fn count_rank(&self, rank: Rank) -> usize {
+        self.remaining().iter().filter(|c| c.rank == rank).count()
+    }
+
+    // into_cards returns the undealt cards, top card first
+    pub fn into_cards(mut self) -> Vec<Card> {
+        self.cards.split_off(self.next_card)
+    }
+
+    // verify checks that the cards are exactly this deck, dealt cards
+    // included, in some order
+    pub fn verify(&self, cards: &[Card]) -> Result<(), CardError> {
+        if cards.len() != self.cards.len() {
+            return Err(CardError::WrongDeckSize {
+                expected: self.cards.len(),
+                actual: cards.len(),
+            });
+        }
+        let expected = tally(&self.cards);
+        let actual = tally(cards);
+        for card in Card::iter() {
+            let i = card.index() as usize;
+            if actual[i] != expected[i] {
+                return Err(CardError::WrongCardCount {
+                    card,
+                    expected: expected[i],
+                    actual: actual[i],
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn tally(cards: &[Card]) -> [usize; DISTINCT_CARDS as usize] {
+    let mut counts = [0; DISTINCT_CARDS as usize];
+    for card in cards {
+        counts[card.index() as usize] += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a deterministic rng that is good enough to move every card
+    struct Lcg(u64);
+
+    impl Rng for Lcg {
+        fn below(&mut self, bound: usize) -> usize {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1);
+            ((self.0 >> 33) % bound as u64) as usize
+        }
+    }
+
+    #[test]
+    fn composition() {
+        let deck = Deck::new(2);
+        assert_eq!(deck.len(), 104);
+        assert_eq!(deck.count_suit(Suit::Hearts), 26);
+        assert_eq!(deck.count_rank(Rank::Queen), 8);
+
+        let spades = Deck::with_suits(8, &[Suit::Spades]);
+        assert_eq!(spades.len(), 104);
+        assert_eq!(spades.count_suit(Suit::Spades), 104);
+        assert_eq!(
+            spades.count(Card {
+                suit: Suit::Spades,
+                rank: Rank::Ace
+            }),
+            8
+        );
+    }
+
+    #[test]
+    fn shuffle_and_deal() {
+        let standard = Deck::new(2);
+        let mut deck = standard.clone();
+        deck.shuffle(&mut Lcg(7));
+        assert_ne!(deck, standard);
+        assert!(standard.verify(deck.remaining()).is_ok());
+
+        let top = deck.remaining()[0];
+        assert_eq!(deck.deal(), Some(top));
+        assert_eq!(deck.len(), 103);
+        assert_eq!(deck.count(top), 1);
+        assert_eq!(deck.clone().into_cards().len(), 103);
+        while deck.deal().is_some() {}
+        assert!(deck.is_empty());
+    }
+
+    #[test]
+    fn verify() {
+        let deck = Deck::new(2);
+        let mut cards = deck.clone().into_cards();
+        assert!(deck.verify(&cards).is_ok());
+        cards[0] = cards[1];
+        assert!(matches!(
+            deck.verify(&cards),
+            Err(CardError::WrongCardCount { .. })
+        ));
+        cards.pop();
+        assert!(matches!(
+            deck.verify(&cards),
+            Err(CardError::WrongDeckSize { .. })
+        ));
+    }
+}
@@ -0,0 +1,27 @@
+use thiserror::Error;
+
+/// CardError is the base class for all errors that occur handling cards.
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum CardError {
+    #[error("invalid rank '{}'", text)]
+    InvalidRank { text: String },
+
+    #[error("invalid suit '{}'", text)]
+    InvalidSuit { text: String },
+
+    #[error("invalid card '{}'", text)]
+    InvalidCard { text: String },
+
+    #[error("deck has {} cards, expected {}", actual, expected)]
+    WrongDeckSize { expected: usize, actual: usize },
+
+    #[error("deck has {} of {}, expected {}", actual, card, expected)]
+    WrongCardCount {
+        card: crate::Card,
+        expected: usize,
+        actual: usize,
+    },
+
+    #[error("invalid style '{}'", text)]
+    InvalidStyle { text: String },
+}
@@ -1,11 +1,21 @@
 // cards definitions
 
+use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::str::FromStr;
 
+use error::CardError;
+
+pub mod deck;
+pub mod error;
 pub mod rank;
+pub mod render;
 pub mod suit;
 
-#[derive(Debug, PartialOrd, PartialEq, Clone, Copy)]
+// the number of distinct cards, and so the bound on Card::index
+pub const DISTINCT_CARDS: u8 = 52;
+
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct Card {
     pub suit: suit::Suit,
     pub rank: rank::Rank,
@@ -17,6 +27,41 @@ impl fmt::Display for Card {
     }
 }
 
+impl FromStr for Card {
+    type Err = CardError;
+
+    // accepts rank then suit ("QH", "10s", "Ts", "Q\u{2665}"),
+    // suit then rank ("\u{2665}Q") and the display form "( Q H)"
+    fn from_str(s: &str) -> Result<Card, CardError> {
+        let invalid = || CardError::InvalidCard {
+            text: s.to_string(),
+        };
+        let trimmed = s.trim();
+        let inner = match trimmed.strip_prefix('(') {
+            Some(rest) => rest.strip_suffix(')').ok_or_else(invalid)?,
+            None => trimmed,
+        };
+        let chars: Vec<char> = inner.chars().filter(|c| !c.is_whitespace()).collect();
+        if chars.len() < 2 {
+            return Err(invalid());
+        }
+
+        // only the suit symbols can lead, the letters would be ambiguous
+        let (rank, suit): (String, String) = if chars[0].is_ascii() {
+            let (rank, suit) = chars.split_at(chars.len() - 1);
+            (rank.iter().collect(), suit.iter().collect())
+        } else {
+            let (suit, rank) = chars.split_at(1);
+            (rank.iter().collect(), suit.iter().collect())
+        };
+
+        Ok(Card {
+            suit: suit.parse().map_err(|_| invalid())?,
+            rank: rank.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
 impl From<Card> for [u8; 2] {
     fn from(card: Card) -> [u8; 2] {
         [card.suit as u8, card.rank as u8]
@@ -37,32 +82,95 @@ pub fn successor(c: Card) -> Option<Card> {
         })
 }
 
+pub fn predecessor(c: Card) -> Option<Card> {
+    rank::predecessor(c.rank)
+        .map(|r| Card {
+            suit: c.suit,
+            rank: r,
+        })
+        .or_else(|| {
+            suit::predecessor(c.suit).map(|s| Card {
+                suit: s,
+                rank: rank::last(),
+            })
+        })
+}
+
 pub struct Iter<Card> {
-    current: Option<Card>,
+    front: Option<Card>,
+    back: Option<Card>,
 }
 
 impl Iterator for Iter<Card> {
     type Item = Card;
 
     fn next(&mut self) -> Option<Card> {
-        let prev = self.current;
+        let prev = self.front;
         if let Some(previous) = prev {
-            self.current = successor(previous);
-            prev
-        } else {
-            None
+            if prev == self.back {
+                self.front = None;
+                self.back = None;
+            } else {
+                self.front = successor(previous);
+            }
         }
+        prev
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = match (self.front, self.back) {
+            (Some(front), Some(back)) => (back.index() - front.index() + 1) as usize,
+            _ => 0,
+        };
+        (len, Some(len))
     }
 }
 
+impl DoubleEndedIterator for Iter<Card> {
+    fn next_back(&mut self) -> Option<Card> {
+        let prev = self.back;
+        if let Some(previous) = prev {
+            if prev == self.front {
+                self.front = None;
+                self.back = None;
+            } else {
+                self.back = predecessor(previous);
+            }
+        }
+        prev
+    }
+}
+
+impl ExactSizeIterator for Iter<Card> {}
+
 impl Card {
     pub fn iter() -> Iter<Card> {
         Iter {
-            current: Some(Card {
+            front: Some(Card {
                 suit: suit::first(),
                 rank: rank::first(),
             }),
+            back: Some(Card {
+                suit: suit::last(),
+                rank: rank::last(),
+            }),
+        }
+    }
+
+    // index is a compact encoding in 0..DISTINCT_CARDS that follows
+    // card order, for tables and bitsets
+    pub fn index(&self) -> u8 {
+        self.suit.index() * 13 + self.rank.value() - 1
+    }
+
+    pub fn from_index(index: u8) -> Option<Card> {
+        if index >= DISTINCT_CARDS {
+            return None;
         }
+        Some(Card {
+            suit: suit::Suit::from_index(index / 13)?,
+            rank: rank::Rank::from_value(index % 13 + 1)?,
+        })
     }
 }
 
@@ -216,6 +324,74 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse() {
+        for (text, expected) in [
+            ("QH", Some((Queen, Hearts))),
+            ("qh", Some((Queen, Hearts))),
+            ("10s", Some((Ten, Spades))),
+            ("Ts", Some((Ten, Spades))),
+            ("XS", Some((Ten, Spades))),
+            ("\u{2665}Q", Some((Queen, Hearts))),
+            ("Q\u{2665}", Some((Queen, Hearts))),
+            ("( Q H)", Some((Queen, Hearts))),
+            ("(10 D)", Some((Ten, Diamonds))),
+            (" A C ", Some((Ace, Clubs))),
+            ("", None),
+            ("Q", None),
+            ("QX", None),
+            ("11H", None),
+            ("(QH", None),
+        ]
+        .iter()
+        {
+            let result = text.parse::<Card>().ok().map(|c| (c.rank, c.suit));
+            assert_eq!(result, *expected, "{:?}", text);
+        }
+
+        for card in Card::iter() {
+            assert_eq!(card.to_string().parse::<Card>(), Ok(card));
+        }
+    }
+
+    #[test]
+    fn index_round_trip() {
+        use std::collections::HashSet;
+
+        let mut seen = HashSet::<Card>::new();
+        for (i, card) in Card::iter().enumerate() {
+            assert_eq!(card.index() as usize, i);
+            assert_eq!(Card::from_index(card.index()), Some(card));
+            assert!(seen.insert(card));
+        }
+        assert_eq!(seen.len(), DISTINCT_CARDS as usize);
+        assert_eq!(Card::from_index(DISTINCT_CARDS), None);
+    }
+
+    #[test]
+    fn iterators() {
+        assert_eq!(Card::iter().len(), 52);
+        assert_eq!(super::rank::iter().len(), 13);
+        assert_eq!(super::suit::iter().len(), 4);
+
+        let mut forward: Vec<Card> = Card::iter().collect();
+        let backward: Vec<Card> = Card::iter().rev().collect();
+        forward.reverse();
+        assert_eq!(forward, backward);
+
+        let mut ranks = super::rank::iter();
+        assert_eq!(ranks.next(), Some(Ace));
+        assert_eq!(ranks.next_back(), Some(King));
+        assert_eq!(ranks.len(), 11);
+        let middle: Vec<_> = ranks.collect();
+        assert_eq!(middle.first(), Some(&Two));
+        assert_eq!(middle.last(), Some(&Queen));
+
+        let mut sorted = backward;
+        sorted.sort();
+        assert_eq!(sorted, Card::iter().collect::<Vec<Card>>());
+    }
+
     #[test]
     fn card_order() {
         for (c1, c2) in [
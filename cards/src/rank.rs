@@ -1,6 +1,10 @@
+use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::str::FromStr;
 
-#[derive(Debug, PartialOrd, PartialEq, Clone, Copy)]
+use super::error::CardError;
+
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum Rank {
     Ace,
     Two,
@@ -60,6 +64,34 @@ impl From<Rank> for u8 {
     }
 }
 
+impl FromStr for Rank {
+    type Err = CardError;
+
+    // accepts the display form and the usual alternatives for ten:
+    // "Q", " Q", "q", "10", "T", "X"
+    fn from_str(s: &str) -> Result<Rank, CardError> {
+        use self::Rank::*;
+        match s.trim().to_ascii_uppercase().as_str() {
+            "A" => Ok(Ace),
+            "2" => Ok(Two),
+            "3" => Ok(Three),
+            "4" => Ok(Four),
+            "5" => Ok(Five),
+            "6" => Ok(Six),
+            "7" => Ok(Seven),
+            "8" => Ok(Eight),
+            "9" => Ok(Nine),
+            "10" | "T" | "X" => Ok(Ten),
+            "J" => Ok(Jack),
+            "Q" => Ok(Queen),
+            "K" => Ok(King),
+            _ => Err(CardError::InvalidRank {
+                text: s.to_string(),
+            }),
+        }
+    }
+}
+
 pub fn successor(r: Rank) -> Option<Rank> {
     use self::Rank::*;
     match r {
@@ -79,17 +111,54 @@ pub fn successor(r: Rank) -> Option<Rank> {
     }
 }
 
+impl Rank {
+    // value is the face value with Ace low: Ace is 1, King is 13
+    pub fn value(&self) -> u8 {
+        *self as u8 + 1
+    }
+
+    pub fn from_value(value: u8) -> Option<Rank> {
+        use self::Rank::*;
+        match value {
+            1 => Some(Ace),
+            2 => Some(Two),
+            3 => Some(Three),
+            4 => Some(Four),
+            5 => Some(Five),
+            6 => Some(Six),
+            7 => Some(Seven),
+            8 => Some(Eight),
+            9 => Some(Nine),
+            10 => Some(Ten),
+            11 => Some(Jack),
+            12 => Some(Queen),
+            13 => Some(King),
+            _ => None,
+        }
+    }
+}
+
+pub fn predecessor(r: Rank) -> Option<Rank> {
+    Rank::from_value(r.value() - 1)
+}
+
 pub fn first() -> Rank {
     Rank::Ace
 }
 
+pub fn last() -> Rank {
+    Rank::King
+}
+
 pub struct Iter<Rank> {
-    current: Option<Rank>,
+    front: Option<Rank>,
+    back: Option<Rank>,
 }
 
 pub fn iter() -> Iter<Rank> {
     Iter {
-        current: Some(first()),
+        front: Some(first()),
+        back: Some(last()),
     }
 }
 
@@ -97,12 +166,40 @@ impl Iterator for Iter<Rank> {
     type Item = Rank;
 
     fn next(&mut self) -> Option<Rank> {
-        let prev = self.current;
+        let prev = self.front;
         if let Some(previous) = prev {
-            self.current = successor(previous);
-            prev
-        } else {
-            None
+            if prev == self.back {
+                self.front = None;
+                self.back = None;
+            } else {
+                self.front = successor(previous);
+            }
         }
+        prev
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = match (self.front, self.back) {
+            (Some(front), Some(back)) => (back.value() - front.value() + 1) as usize,
+            _ => 0,
+        };
+        (len, Some(len))
     }
 }
+
+impl DoubleEndedIterator for Iter<Rank> {
+    fn next_back(&mut self) -> Option<Rank> {
+        let prev = self.back;
+        if let Some(previous) = prev {
+            if prev == self.front {
+                self.front = None;
+                self.back = None;
+            } else {
+                self.back = predecessor(previous);
+            }
+        }
+        prev
+    }
+}
+
+impl ExactSizeIterator for Iter<Rank> {}
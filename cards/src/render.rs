@@ -0,0 +1,147 @@
+// render draws cards for terminals in a choice of styles.
+//
+// A Style is written as its symbols optionally followed by "+color":
+// "plain", "unicode", "glyph", "unicode+color". "color" on its own
+// means "unicode+color".
+
+use std::str::FromStr;
+
+use super::error::CardError;
+use super::rank::Rank;
+use super::suit::{Color, Suit};
+use super::Card;
+
+const RED: &str = "\u{1b}[31m";
+const RESET: &str = "\u{1b}[0m";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symbols {
+    /// the Display form: ( Q H)
+    Letters,
+    /// suit symbols: ( Q ♥)
+    Unicode,
+    /// the Unicode playing card block: 🂽
+    Glyphs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Style {
+    pub symbols: Symbols,
+    /// red suits in red, black suits in the terminal's own color
+    pub color: bool,
+}
+
+impl Default for Style {
+    fn default() -> Style {
+        Style {
+            symbols: Symbols::Letters,
+            color: false,
+        }
+    }
+}
+
+impl FromStr for Style {
+    type Err = CardError;
+
+    fn from_str(s: &str) -> Result<Style, CardError> {
+        let invalid = || CardError::InvalidStyle {
+            text: s.to_string(),
+        };
+        let (symbols, color) = match s.trim().split_once('+') {
+            None => (s.trim(), false),
+            Some((symbols, "color")) => (symbols, true),
+            Some(_) => return Err(invalid()),
+        };
+        let (symbols, color) = match symbols {
+            "plain" => (Symbols::Letters, color),
+            "unicode" => (Symbols::Unicode, color),
+            "glyph" => (Symbols::Glyphs, color),
+            "color" if !color => (Symbols::Unicode, true),
+            _ => return Err(invalid()),
+        };
+        Ok(Style { symbols, color })
+    }
+}
+
+impl Style {
+    pub fn card(&self, card: Card) -> String {
+        let text = match self.symbols {
+            Symbols::Letters => card.to_string(),
+            Symbols::Unicode => format!("({} {})", card.rank, suit_symbol(card.suit)),
+            Symbols::Glyphs => glyph(card).to_string(),
+        };
+        if self.color && card.suit.color() == Color::Red {
+            format!("{}{}{}", RED, text, RESET)
+        } else {
+            text
+        }
+    }
+
+    pub fn hidden(&self) -> String {
+        match self.symbols {
+            Symbols::Letters | Symbols::Unicode => "(----)".to_string(),
+            Symbols::Glyphs => "\u{1f0a0}".to_string(),
+        }
+    }
+
+    // width is the number of terminal columns a card or a hidden card
+    // occupies, not counting color escapes
+    pub fn width(&self) -> usize {
+        match self.symbols {
+            Symbols::Letters | Symbols::Unicode => 6,
+            Symbols::Glyphs => 1,
+        }
+    }
+}
+
+fn suit_symbol(suit: Suit) -> char {
+    match suit {
+        Suit::Clubs => '\u{2663}',
+        Suit::Diamonds => '\u{2666}',
+        Suit::Hearts => '\u{2665}',
+        Suit::Spades => '\u{2660}',
+    }
+}
+
+// the playing card block has a row per suit and skips the Knight
+// between Jack and Queen
+fn glyph(card: Card) -> char {
+    let row: u32 = match card.suit {
+        Suit::Spades => 0x1f0a0,
+        Suit::Hearts => 0x1f0b0,
+        Suit::Diamonds => 0x1f0c0,
+        Suit::Clubs => 0x1f0d0,
+    };
+    let offset = match card.rank {
+        Rank::Queen | Rank::King => card.rank.value() as u32 + 1,
+        rank => rank.value() as u32,
+    };
+    std::char::from_u32(row + offset).unwrap_or('?')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn styles() {
+        let queen = Card {
+            suit: Suit::Hearts,
+            rank: Rank::Queen,
+        };
+        for (text, rendered) in [
+            ("plain", "( Q H)"),
+            ("unicode", "( Q \u{2665})"),
+            ("glyph", "\u{1f0bd}"),
+            ("color", "\u{1b}[31m( Q \u{2665})\u{1b}[0m"),
+            ("plain+color", "\u{1b}[31m( Q H)\u{1b}[0m"),
+        ]
+        .iter()
+        {
+            let style: Style = text.parse().unwrap();
+            assert_eq!(style.card(queen), *rendered, "{}", text);
+        }
+        assert!("fancy".parse::<Style>().is_err());
+        assert!("unicode+bold".parse::<Style>().is_err());
+    }
+}
@@ -1,6 +1,10 @@
+use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::str::FromStr;
 
-#[derive(Debug, PartialOrd, PartialEq, Clone, Copy)]
+use super::error::CardError;
+
+#[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum Suit {
     Clubs,
     Diamonds,
@@ -31,6 +35,23 @@ impl From<Suit> for u8 {
     }
 }
 
+impl FromStr for Suit {
+    type Err = CardError;
+
+    // accepts the letters in either case and the Unicode suit symbols
+    fn from_str(s: &str) -> Result<Suit, CardError> {
+        match s.trim() {
+            "C" | "c" | "\u{2663}" | "\u{2667}" => Ok(Suit::Clubs),
+            "D" | "d" | "\u{2666}" | "\u{2662}" => Ok(Suit::Diamonds),
+            "H" | "h" | "\u{2665}" | "\u{2661}" => Ok(Suit::Hearts),
+            "S" | "s" | "\u{2660}" | "\u{2664}" => Ok(Suit::Spades),
+            _ => Err(CardError::InvalidSuit {
+                text: s.to_string(),
+            }),
+        }
+    }
+}
+
 pub fn successor(s: Suit) -> Option<Suit> {
     use self::Suit::*;
     match s {
@@ -41,30 +62,99 @@ pub fn successor(s: Suit) -> Option<Suit> {
     }
 }
 
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Color {
+    Red,
+    Black,
+}
+
+impl Suit {
+    pub fn color(&self) -> Color {
+        match self {
+            Suit::Diamonds | Suit::Hearts => Color::Red,
+            Suit::Clubs | Suit::Spades => Color::Black,
+        }
+    }
+
+    // index is the position in suit order: Clubs is 0, Spades is 3
+    pub fn index(&self) -> u8 {
+        *self as u8
+    }
+
+    pub fn from_index(index: u8) -> Option<Suit> {
+        use self::Suit::*;
+        match index {
+            0 => Some(Clubs),
+            1 => Some(Diamonds),
+            2 => Some(Hearts),
+            3 => Some(Spades),
+            _ => None,
+        }
+    }
+}
+
+pub fn predecessor(s: Suit) -> Option<Suit> {
+    s.index().checked_sub(1).and_then(Suit::from_index)
+}
+
 pub fn first() -> Suit {
     Suit::Clubs
 }
 
+pub fn last() -> Suit {
+    Suit::Spades
+}
+
 pub fn iter() -> Iter<Suit> {
     Iter {
-        current: Some(first()),
+        front: Some(first()),
+        back: Some(last()),
     }
 }
 
 pub struct Iter<Suit> {
-    current: Option<Suit>,
+    front: Option<Suit>,
+    back: Option<Suit>,
 }
 
 impl Iterator for Iter<Suit> {
     type Item = Suit;
 
     fn next(&mut self) -> Option<Suit> {
-        let prev = self.current;
+        let prev = self.front;
         if let Some(previous) = prev {
-            self.current = successor(previous);
-            prev
-        } else {
-            None
+            if prev == self.back {
+                self.front = None;
+                self.back = None;
+            } else {
+                self.front = successor(previous);
+            }
         }
+        prev
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = match (self.front, self.back) {
+            (Some(front), Some(back)) => (back.index() - front.index() + 1) as usize,
+            _ => 0,
+        };
+        (len, Some(len))
     }
 }
+
+impl DoubleEndedIterator for Iter<Suit> {
+    fn next_back(&mut self) -> Option<Suit> {
+        let prev = self.back;
+        if let Some(previous) = prev {
+            if prev == self.front {
+                self.front = None;
+                self.back = None;
+            } else {
+                self.back = predecessor(previous);
+            }
+        }
+        prev
+    }
+}
+
+impl ExactSizeIterator for Iter<Suit> {}
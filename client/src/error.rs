@@ -1,22 +1,29 @@
-use game::delta::Delta;
 use game::error::GameError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum ClientError {
-    #[error("unknown Delta {:?}", delta)]
-    UnknownDelta { delta: Delta },
-
     #[error("no move found in origin")]
     NoMove {},
 
     #[error("bottom card in dest is not visible")]
     BottomNotVisible {},
 
+    #[error("invalid broadcast frame: {}", reason)]
+    InvalidFrame { reason: String },
+
     /// Represents GameError
     #[error(transparent)]
     GameError(#[from] GameError),
 
+    /// Represents serde_json::Error
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+
+    /// Represents io::Error
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+
     /// Represents HexError
     #[error(transparent)]
     HexError(#[from] hex::FromHexError),
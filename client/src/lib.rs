@@ -1,4 +1,5 @@
 use crate::error::ClientError;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 
@@ -6,18 +7,24 @@ use game::delta::Delta;
 use game::Move;
 
 pub mod error;
+pub mod race;
+pub mod session;
+pub mod spectator;
+pub mod state;
 
-const WIDTH: usize = 10;
 type Column = Vec<Option<cards::Card>>;
 
 #[derive(Debug)]
 pub struct Client {
     remote: game::Game,
     used: HashMap<String, Move>,
+    timer: session::Timer,
+    stats: session::SessionStats,
+    broadcast: Option<spectator::Broadcast>,
     pub local: Vec<Column>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PotentialMove {
     pub mv: Move,
     pub is_used: bool,
@@ -28,15 +35,64 @@ impl Client {
         client_from_game(game::Game::new()?)
     }
 
+    // from_hex accepts both legacy (bare hex) and versioned ("v1-<hex>") seeds
     pub fn from_hex(hex_seed: &str) -> Result<Client, ClientError> {
-        let seed = game::seed::from_hex(hex_seed)?;
+        let seed: game::seed::Seed = hex_seed.parse()?;
 
         let game = game::Game::from_seed(seed)?;
         client_from_game(game)
     }
 
+    pub fn from_deck(deck: Vec<cards::Card>) -> Result<Client, ClientError> {
+        client_from_game(game::Game::from_deck(deck)?)
+    }
+
+    pub fn from_position(position: game::position::Position) -> Result<Client, ClientError> {
+        client_from_game(game::Game::from_position(position)?)
+    }
+
+    // from_layout deals a game of any variant; a random one without a seed
+    pub fn from_layout(
+        layout: game::layout::Layout,
+        hex_seed: Option<&str>,
+    ) -> Result<Client, ClientError> {
+        let seed = match hex_seed {
+            Some(hex_seed) => hex_seed.parse()?,
+            None => game::seed::from_random(),
+        };
+        client_from_game(game::Game::from_layout_seed(layout, seed)?)
+    }
+
+    pub fn layout(&self) -> &game::layout::Layout {
+        self.remote.layout()
+    }
+
+    // with_undo_mode sets how often the new game may be undone
+    pub fn with_undo_mode(mut self, undo_mode: game::undo::UndoMode) -> Client {
+        self.remote = self.remote.with_undo_mode(undo_mode);
+        self
+    }
+
+    pub fn undo_mode(&self) -> game::undo::UndoMode {
+        self.remote.undo_mode()
+    }
+
+    // undos_left is None when the undo mode sets no limit
+    pub fn undos_left(&self) -> Option<usize> {
+        self.undo_mode().remaining(self.remote.undos())
+    }
+
+    // subscribe registers an observer for the game's events
+    pub fn subscribe(&mut self, observer: Box<dyn game::event::Observer>) {
+        self.remote.subscribe(observer);
+    }
+
+    // seed is "custom" for a game dealt from an explicit deck or position
     pub fn seed(&self) -> String {
-        hex::encode(self.remote.seed())
+        match self.remote.seed() {
+            Some(seed) => seed.to_string(),
+            None => "custom".to_string(),
+        }
     }
 
     pub fn total_cards(&self) -> usize {
@@ -51,27 +107,58 @@ impl Client {
         self.remote.checkpoints()
     }
 
-    pub fn deal(&mut self) -> std::result::Result<(), ClientError> {
-        for delta in self.remote.deal()? {
-            use game::delta::Delta::*;
-            match delta {
-                AppendCard { index: i, card: c } => self.local[i].push(Some(c)),
-                _ => {
-                    return Err(ClientError::UnknownDelta { delta });
-                }
-            }
-        }
+    pub fn completed_runs(&self) -> usize {
+        self.remote.completed_runs()
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.remote.is_won()
+    }
 
+    // score is the usual Spider score: 500 to start, one less for each
+    // move, the undo mode's cost for each undo and 100 more for each
+    // completed run
+    pub fn score(&self) -> i64 {
+        500 - self.stats.moves as i64 - self.stats.undos as i64 * self.undo_mode().cost()
+            + 100 * self.completed_runs() as i64
+    }
+
+    pub fn deal(&mut self) -> std::result::Result<(), ClientError> {
+        let deltas = self.remote.deal()?;
+        self.apply_deltas(&deltas)?;
+        self.stats.deals += 1;
+        self.timer.resume();
+        self.send_update(&deltas);
         Ok(())
     }
 
     pub fn undo(&mut self) -> Result<(), ClientError> {
         let deltas = self.remote.undo()?;
-        self.apply_deltas(deltas)?;
+        self.apply_deltas(&deltas)?;
         self.used.remove(&self.digest());
+        self.stats.undos += 1;
+        self.timer.resume();
+        self.send_update(&deltas);
         Ok(())
     }
 
+    // pause stops the play timer until resume, or the next move, deal or undo
+    pub fn pause(&mut self) {
+        self.timer.pause();
+    }
+
+    pub fn resume(&mut self) {
+        self.timer.resume();
+    }
+
+    pub fn session_stats(&self) -> session::SessionStats {
+        session::SessionStats {
+            elapsed: self.timer.elapsed(),
+            paused: self.timer.is_paused(),
+            ..self.stats
+        }
+    }
+
     pub fn digest(&self) -> String {
         let mut hasher = Sha256::new();
         for column in &self.local {
@@ -91,106 +178,76 @@ impl Client {
         hex::encode(hasher.result())
     }
 
+    // possible_moves offers, for each pair of columns, the most cards
+    // the rules of the variant allow to move from one onto the other
     pub fn possible_moves(&self) -> Result<Vec<PotentialMove>, ClientError> {
         let is_used = |m| match self.used.get(&self.digest()) {
             Some(u) => u == &m,
             None => false,
         };
+        let rules = self.remote.rules();
         let mut moves = Vec::<PotentialMove>::new();
-        'width: for i in 0..WIDTH {
+        for i in 0..self.local.len() {
             if self.local[i].is_empty() {
-                continue 'width;
-            }
-            let orig = &self.local[i];
-            let mut count = 0;
-            let mut cards = Vec::<cards::Card>::new();
-            'len: for n in (0..orig.len()).rev() {
-                match orig[n] {
-                    None => break,
-                    Some(c) => {
-                        cards.insert(0, c);
-                        if cards::is_descending_run(&cards) {
-                            count += 1;
-                        } else {
-                            cards = cards[1..].to_vec();
-                            break 'len;
-                        }
-                    }
-                }
+                continue;
             }
-            if count == 0 {
+            // the face up cards at the top of the column, bottom card first
+            let mut visible: Vec<cards::Card> =
+                self.local[i].iter().rev().map_while(|c| *c).collect();
+            visible.reverse();
+            if visible.is_empty() {
                 return Err(ClientError::NoMove {});
             }
-            let valid_dest_rank = match cards::rank::successor(cards[0].rank) {
-                None => continue,
-                Some(r) => r,
-            };
-            for j in 0..WIDTH {
+            for j in 0..self.local.len() {
                 if j == i {
                     continue;
                 }
-                let dest = &self.local[j];
-                if dest.is_empty() {
+                let dest = match self.local[j].last() {
+                    None => None,
+                    Some(None) => return Err(ClientError::BottomNotVisible {}),
+                    Some(Some(dc)) => Some(*dc),
+                };
+                let count = (1..=visible.len())
+                    .rev()
+                    .find(|count| rules.is_move_valid(&visible[visible.len() - count..], dest));
+                if let Some(count) = count {
                     let gmv = game::Move {
                         orig_col: i,
                         count,
                         dest_col: j,
                     };
-                    let pmv = PotentialMove {
+                    moves.push(PotentialMove {
                         mv: gmv,
                         is_used: is_used(gmv),
-                    };
-                    moves.push(pmv);
-                    continue;
-                }
-                match dest[dest.len() - 1] {
-                    None => {
-                        return Err(ClientError::BottomNotVisible {});
-                    }
-                    Some(dc) => {
-                        if dc.rank == valid_dest_rank {
-                            let gmv = game::Move {
-                                orig_col: i,
-                                count,
-                                dest_col: j,
-                            };
-                            let move_is_used = is_used(gmv);
-                            let pmv = PotentialMove {
-                                mv: gmv,
-                                is_used: move_is_used,
-                            };
-                            moves.push(pmv);
-                        }
-                    }
+                    });
                 }
             }
         }
         Ok(moves)
     }
 
+    // is_move_valid accepts any move the rules allow, not only those
+    // possible_moves offers
+    pub fn is_move_valid(&self, m: &game::Move) -> bool {
+        self.remote.is_move_valid(m)
+    }
+
     pub fn move_cards(&mut self, m: game::Move) -> Result<(), ClientError> {
         let pre_move_digest = self.digest();
 
         let deltas = self.remote.move_cards(m)?;
-        self.apply_deltas(deltas)?;
+        self.apply_deltas(&deltas)?;
 
         self.used.insert(pre_move_digest, m);
+        self.stats.moves += 1;
+        self.timer.resume();
+        self.send_update(&deltas);
 
         Ok(())
     }
 
-    fn apply_deltas(&mut self, deltas: Vec<Delta>) -> Result<(), ClientError> {
-        for delta in deltas {
-            match delta {
-                Delta::HiddenCard { index: i } => self.local[i].push(None),
-                Delta::AppendCard { index: i, card: c } => self.local[i].push(Some(c)),
-                Delta::PopCard { index: i } => {
-                    self.local[i].pop();
-                }
-            }
-        }
-
-        Ok(())
+    fn apply_deltas(&mut self, deltas: &[Delta]) -> Result<(), ClientError> {
+        spectator::apply_deltas(&mut self.local, deltas)
     }
 }
 
@@ -198,16 +255,239 @@ fn client_from_game(game: game::Game) -> Result<Client, ClientError> {
     let mut client = Client {
         remote: game,
         used: HashMap::new(),
+        timer: session::Timer::start(),
+        stats: session::SessionStats::default(),
+        broadcast: None,
         local: Vec::new(),
     };
 
-    for _ in 0..WIDTH {
+    for _ in 0..client.remote.width() {
         client.local.push(Vec::new());
     }
 
     let deltas = client.remote.initial_deltas();
 
-    client.apply_deltas(deltas)?;
+    client.apply_deltas(&deltas)?;
 
     Ok(client)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const WIDTH: usize = 10;
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        Deal,
+        // index into possible_moves, modulo its length
+        Move(usize),
+        Undo,
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            1 => Just(Op::Deal),
+            6 => any::<usize>().prop_map(Op::Move),
+            2 => Just(Op::Undo),
+        ]
+    }
+
+    // local_matches_remote checks that the client's view is exactly the
+    // engine's columns with the hidden cards masked
+    fn local_matches_remote(client: &Client) -> bool {
+        let expected: Vec<Column> = client
+            .remote
+            .position()
+            .columns
+            .iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .map(|column_card| match column_card {
+                        game::ColumnCard::Hidden { .. } => None,
+                        game::ColumnCard::Visible { card } => Some(*card),
+                    })
+                    .collect()
+            })
+            .collect();
+        client.local == expected
+    }
+
+    #[test]
+    fn deal_and_undo() {
+        let mut client = Client::from_hex("v1-0123456789abcdef0123456789abcdef").unwrap();
+        let before = client.local.clone();
+        client.deal().unwrap();
+        assert!(local_matches_remote(&client));
+        for (i, column) in client.local.iter().enumerate() {
+            assert_eq!(column.len(), before[i].len() + 1);
+        }
+        client.undo().unwrap();
+        assert_eq!(client.local, before);
+    }
+
+    #[test]
+    fn events() {
+        use game::event::Event;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let events: Rc<RefCell<Vec<Event>>> = Rc::new(RefCell::new(Vec::new()));
+        let mut client = Client::from_hex("v1-0123456789abcdef0123456789abcdef").unwrap();
+        let log = events.clone();
+        client.subscribe(Box::new(move |event: &Event| {
+            log.borrow_mut().push(event.clone())
+        }));
+
+        client.deal().unwrap();
+        let m = client.possible_moves().unwrap()[0].mv;
+        client.move_cards(m).unwrap();
+        client.undo().unwrap();
+
+        let events = events.borrow();
+        assert_eq!(events.len(), 3);
+        match &events[0] {
+            Event::Dealt { cards } => {
+                let tops: Vec<cards::Card> = client
+                    .local
+                    .iter()
+                    .map(|c| c.last().unwrap().unwrap())
+                    .collect();
+                assert_eq!(*cards, tops);
+            }
+            event => panic!("expected a deal, got {:?}", event),
+        }
+        assert_eq!(events[1], Event::CardsMoved { mv: m });
+        assert!(matches!(events[2], Event::Undone { .. }));
+    }
+
+    #[test]
+    fn session_stats() {
+        let mut client = Client::from_hex("v1-0123456789abcdef0123456789abcdef").unwrap();
+        client.deal().unwrap();
+        let m = client.possible_moves().unwrap()[0].mv;
+        client.move_cards(m).unwrap();
+        client.undo().unwrap();
+        let stats = client.session_stats();
+        assert_eq!((stats.moves, stats.undos, stats.deals), (1, 1, 1));
+        assert_eq!(client.score(), 498);
+
+        client.pause();
+        let paused = client.session_stats();
+        assert!(paused.paused);
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert_eq!(client.session_stats().elapsed, paused.elapsed);
+
+        client.undo().unwrap();
+        let resumed = client.session_stats();
+        assert!(!resumed.paused);
+        assert_eq!(resumed.deals, 1);
+        assert_eq!(resumed.undos, 2);
+    }
+
+    #[test]
+    fn undo_mode() {
+        use game::undo::UndoMode;
+
+        let client = || Client::from_hex("v1-0123456789abcdef0123456789abcdef").unwrap();
+        let mut costly = client().with_undo_mode(UndoMode::Cost(50));
+        costly.deal().unwrap();
+        costly.undo().unwrap();
+        assert_eq!(costly.score(), 450);
+        assert_eq!(costly.undos_left(), None);
+
+        let mut limited = client().with_undo_mode(UndoMode::Limit(1));
+        limited.deal().unwrap();
+        limited.deal().unwrap();
+        limited.undo().unwrap();
+        assert_eq!(limited.undos_left(), Some(0));
+        let local = limited.local.clone();
+        assert!(matches!(
+            limited.undo(),
+            Err(ClientError::GameError(
+                game::error::GameError::UndoLimitReached { .. }
+            ))
+        ));
+        assert_eq!(limited.local, local);
+        assert_eq!(limited.session_stats().undos, 1);
+    }
+
+    // a position where the first card dealt, the Ace of spades, completes
+    // the King to Two of spades run lying over a hidden card in column 0
+    #[test]
+    fn deal_and_undo_with_completion() {
+        use cards::suit::Suit::*;
+        use game::ColumnCard::*;
+
+        let mut rest = game::source::standard_deck();
+        let mut run: Vec<cards::Card> = cards::rank::iter()
+            .map(|rank| cards::Card { suit: Spades, rank })
+            .collect();
+        run.reverse();
+        for card in &run {
+            rest.remove(rest.iter().position(|c| c == card).unwrap());
+        }
+        let ace = run.pop().unwrap();
+        let mut stock = vec![ace];
+        stock.extend(rest.drain(..49));
+        let mut columns: Vec<Vec<game::ColumnCard>> = vec![Vec::new(); WIDTH];
+        columns[0].push(Hidden {
+            card: rest.pop().unwrap(),
+        });
+        columns[0].extend(run.into_iter().map(|card| Visible { card }));
+        for (i, card) in rest.into_iter().enumerate() {
+            columns[1 + i % (WIDTH - 1)].push(Visible { card });
+        }
+        let position = game::position::Position {
+            columns,
+            stock,
+            completed: Vec::new(),
+        };
+
+        let mut client = Client::from_position(position).unwrap();
+        let before = client.local.clone();
+        client.deal().unwrap();
+        assert!(local_matches_remote(&client));
+        assert_eq!(client.local[0].len(), 1);
+        assert!(client.local[0][0].is_some());
+
+        client.undo().unwrap();
+        assert!(local_matches_remote(&client));
+        assert_eq!(client.local, before);
+    }
+
+    proptest! {
+        #[test]
+        fn local_tracks_remote(
+            variant in 0..game::layout::Layout::builtin().len(),
+            seed in any::<[u8; 16]>(),
+            ops in prop::collection::vec(op(), 0..150),
+        ) {
+            let layout = game::layout::Layout::builtin().swap_remove(variant);
+            let seed = game::seed::Seed::new(game::seed::Version::V1, seed);
+            let game = game::Game::from_layout_seed(layout, seed).unwrap();
+            let mut client = client_from_game(game).unwrap();
+            prop_assert!(local_matches_remote(&client));
+            for op in ops {
+                match op {
+                    Op::Deal => {
+                        let _ = client.deal();
+                    }
+                    Op::Move(n) => {
+                        let moves = client.possible_moves().unwrap();
+                        if !moves.is_empty() {
+                            client.move_cards(moves[n % moves.len()].mv).unwrap();
+                        }
+                    }
+                    Op::Undo => {
+                        let _ = client.undo();
+                    }
+                }
+                prop_assert!(local_matches_remote(&client), "{:?}", op);
+            }
+        }
+    }
+}
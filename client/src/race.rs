@@ -0,0 +1,193 @@
+// race deals the same game to several players, each playing a client of
+// their own, and keeps the scoreboard. Players take turns at one keyboard:
+// each player's clock runs only during their turn.
+//
+// The ranking puts winners first, then more completed runs, then the
+// higher score, then the shorter time.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::error::ClientError;
+use super::Client;
+
+#[derive(Debug)]
+pub struct Racer {
+    pub name: String,
+    pub client: Client,
+    // resigned, or out of moves and deals
+    pub finished: bool,
+}
+
+impl Racer {
+    pub fn is_done(&self) -> bool {
+        self.finished || self.client.is_won()
+    }
+}
+
+#[derive(Debug)]
+pub struct Race {
+    seed: String,
+    racers: Vec<Racer>,
+}
+
+// Standing is a racer's line on the scoreboard
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Standing {
+    // 1 for the leader
+    pub rank: usize,
+    pub name: String,
+    pub completed_runs: usize,
+    pub moves: usize,
+    pub elapsed: Duration,
+    pub score: i64,
+    pub won: bool,
+    pub done: bool,
+}
+
+impl Race {
+    // new deals the game of the seed to every player, with their clocks
+    // stopped until their first turn
+    pub fn new(
+        layout: game::layout::Layout,
+        undo_mode: game::undo::UndoMode,
+        hex_seed: &str,
+        names: &[String],
+    ) -> Result<Race, ClientError> {
+        let mut racers: Vec<Racer> = Vec::new();
+        for name in names {
+            let mut client =
+                Client::from_layout(layout.clone(), Some(hex_seed))?.with_undo_mode(undo_mode);
+            client.pause();
+            racers.push(Racer {
+                name: name.clone(),
+                client,
+                finished: false,
+            });
+        }
+        Ok(Race {
+            seed: hex_seed.to_string(),
+            racers,
+        })
+    }
+
+    pub fn seed(&self) -> &str {
+        &self.seed
+    }
+
+    pub fn racers(&self) -> &[Racer] {
+        &self.racers
+    }
+
+    pub fn racer_mut(&mut self, i: usize) -> &mut Racer {
+        &mut self.racers[i]
+    }
+
+    // next_turn is the next racer after i still playing, if any,
+    // which may be i itself
+    pub fn next_turn(&self, i: usize) -> Option<usize> {
+        let n = self.racers.len();
+        (1..=n)
+            .map(|step| (i + step) % n)
+            .find(|j| !self.racers[*j].is_done())
+    }
+
+    pub fn start_turn(&mut self, i: usize) {
+        self.racers[i].client.resume();
+    }
+
+    pub fn end_turn(&mut self, i: usize) {
+        self.racers[i].client.pause();
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.racers.iter().all(|racer| racer.is_done())
+    }
+
+    // scoreboard lists the racers in ranking order
+    pub fn scoreboard(&self) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = self
+            .racers
+            .iter()
+            .map(|racer| {
+                let session = racer.client.session_stats();
+                Standing {
+                    rank: 0,
+                    name: racer.name.clone(),
+                    completed_runs: racer.client.completed_runs(),
+                    moves: session.moves,
+                    elapsed: session.elapsed,
+                    score: racer.client.score(),
+                    won: racer.client.is_won(),
+                    done: racer.is_done(),
+                }
+            })
+            .collect();
+        standings.sort_by(|a, b| {
+            b.won
+                .cmp(&a.won)
+                .then(b.completed_runs.cmp(&a.completed_runs))
+                .then(b.score.cmp(&a.score))
+                .then(a.elapsed.cmp(&b.elapsed))
+        });
+        for (i, standing) in standings.iter_mut().enumerate() {
+            standing.rank = i + 1;
+        }
+        standings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn race() -> Race {
+        let names = vec!["ann".to_string(), "bob".to_string(), "cy".to_string()];
+        Race::new(
+            game::layout::Layout::spider(),
+            game::undo::UndoMode::default(),
+            "v1-0123456789abcdef0123456789abcdef",
+            &names,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn same_deal() {
+        let race = race();
+        let digest = race.racers()[0].client.digest();
+        assert!(race.racers().iter().all(|r| r.client.digest() == digest));
+        assert!(race
+            .racers()
+            .iter()
+            .all(|r| r.client.session_stats().paused));
+    }
+
+    #[test]
+    fn turns_and_ranking() {
+        let mut race = race();
+        assert_eq!(race.next_turn(0), Some(1));
+        assert_eq!(race.next_turn(2), Some(0));
+
+        race.start_turn(1);
+        race.racer_mut(1).client.deal().unwrap();
+        race.end_turn(1);
+        assert!(race.racers()[1].client.session_stats().paused);
+        race.racer_mut(2).client.deal().unwrap();
+        race.racer_mut(2).client.undo().unwrap();
+        race.end_turn(2);
+
+        // cy lost a point for the undo
+        let scoreboard = race.scoreboard();
+        assert_eq!(scoreboard[2].name, "cy");
+        assert_eq!(scoreboard[2].score, 499);
+
+        race.racer_mut(0).finished = true;
+        assert_eq!(race.next_turn(2), Some(1));
+        race.racer_mut(1).finished = true;
+        race.racer_mut(2).finished = true;
+        assert_eq!(race.next_turn(2), None);
+        assert!(race.is_over());
+        assert_eq!(race.scoreboard()[2].rank, 3);
+    }
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+// SessionStats counts what the player has done in this session.
+// Undone moves and deals still count, as does each undo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SessionStats {
+    pub elapsed: Duration,
+    pub paused: bool,
+    pub moves: usize,
+    pub undos: usize,
+    pub deals: usize,
+}
+
+// Timer measures play time, leaving out the time spent paused
+#[derive(Debug, Clone, Copy)]
+pub struct Timer {
+    running_since: Option<Instant>,
+    accumulated: Duration,
+}
+
+impl Timer {
+    pub fn start() -> Timer {
+        Timer {
+            running_since: Some(Instant::now()),
+            accumulated: Duration::default(),
+        }
+    }
+
+    pub fn pause(&mut self) {
+        if let Some(since) = self.running_since.take() {
+            self.accumulated += since.elapsed();
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.running_since.is_none() {
+            self.running_since = Some(Instant::now());
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.running_since.is_none()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        match self.running_since {
+            Some(since) => self.accumulated + since.elapsed(),
+            None => self.accumulated,
+        }
+    }
+}
@@ -0,0 +1,253 @@
+// spectator streams a game to watchers as JSON lines of frames: a start
+// frame with the columns as the player sees them, an update with the deltas
+// of every deal, move and undo, and an end frame. Deltas never name a hidden
+// card, so a watcher sees no more of the game than the player does.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Write;
+
+use game::delta::Delta;
+
+use super::error::ClientError;
+use super::{Client, Column};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "frame", rename_all = "lowercase")]
+pub enum Frame {
+    Start {
+        variant: String,
+        seed: String,
+        width: usize,
+        deltas: Vec<Delta>,
+        status: Status,
+    },
+    Update {
+        deltas: Vec<Delta>,
+        status: Status,
+    },
+    End {
+        status: Status,
+    },
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Status {
+    pub score: i64,
+    pub moves: usize,
+    pub completed_runs: usize,
+    pub cards_remaining: usize,
+    pub won: bool,
+}
+
+// Broadcast is where a client sends its frames
+pub struct Broadcast(Box<dyn Write>);
+
+impl Broadcast {
+    fn send(&mut self, frame: &Frame) -> Result<(), ClientError> {
+        writeln!(self.0, "{}", serde_json::to_string(frame)?)?;
+        self.0.flush()?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Broadcast {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Broadcast")
+    }
+}
+
+impl Client {
+    // broadcast streams the game to out from now on,
+    // starting from the columns as they are
+    pub fn broadcast(&mut self, out: Box<dyn Write>) -> Result<(), ClientError> {
+        let mut deltas: Vec<Delta> = Vec::new();
+        for (index, column) in self.local.iter().enumerate() {
+            for card in column {
+                deltas.push(match card {
+                    Some(card) => Delta::AppendCard { index, card: *card },
+                    None => Delta::HiddenCard { index },
+                });
+            }
+        }
+        let mut broadcast = Broadcast(out);
+        broadcast.send(&Frame::Start {
+            variant: self.layout().name.clone(),
+            seed: self.seed(),
+            width: self.local.len(),
+            deltas,
+            status: self.spectator_status(),
+        })?;
+        self.broadcast = Some(broadcast);
+        Ok(())
+    }
+
+    // end_broadcast tells the watchers the game is over
+    pub fn end_broadcast(&mut self) -> Result<(), ClientError> {
+        let status = self.spectator_status();
+        match self.broadcast.take() {
+            Some(mut broadcast) => broadcast.send(&Frame::End { status }),
+            None => Ok(()),
+        }
+    }
+
+    // send_update stops broadcasting, rather than fail the player's game,
+    // when the watchers have gone
+    pub(crate) fn send_update(&mut self, deltas: &[Delta]) {
+        let status = self.spectator_status();
+        if let Some(broadcast) = self.broadcast.as_mut() {
+            let frame = Frame::Update {
+                deltas: deltas.to_vec(),
+                status,
+            };
+            if broadcast.send(&frame).is_err() {
+                self.broadcast = None;
+            }
+        }
+    }
+
+    fn spectator_status(&self) -> Status {
+        Status {
+            score: self.score(),
+            moves: self.stats.moves,
+            completed_runs: self.completed_runs(),
+            cards_remaining: self.total_cards() - self.cards_dealt(),
+            won: self.is_won(),
+        }
+    }
+}
+
+// Spectator is a watcher's read only view of a broadcast game
+#[derive(Debug, Default, Clone)]
+pub struct Spectator {
+    pub variant: String,
+    pub seed: String,
+    pub local: Vec<Column>,
+    pub status: Status,
+    // the end frame has arrived
+    pub over: bool,
+}
+
+impl Spectator {
+    // apply checks the frame against the columns, since it comes from
+    // outside, and leaves the view as it was if the frame is invalid
+    pub fn apply(&mut self, frame: Frame) -> Result<(), ClientError> {
+        match frame {
+            Frame::Start {
+                variant,
+                seed,
+                width,
+                deltas,
+                status,
+            } => {
+                if width > game::packed::MAX_COLUMNS {
+                    return Err(ClientError::InvalidFrame {
+                        reason: format!("{} columns", width),
+                    });
+                }
+                let mut local = vec![Vec::new(); width];
+                apply_deltas(&mut local, &deltas)?;
+                *self = Spectator {
+                    variant,
+                    seed,
+                    local,
+                    status,
+                    over: false,
+                };
+            }
+            Frame::Update { deltas, status } => {
+                let mut local = self.local.clone();
+                apply_deltas(&mut local, &deltas)?;
+                self.local = local;
+                self.status = status;
+            }
+            Frame::End { status } => {
+                self.status = status;
+                self.over = true;
+            }
+        }
+        Ok(())
+    }
+
+    // read_line applies one line of a broadcast
+    pub fn read_line(&mut self, line: &str) -> Result<(), ClientError> {
+        self.apply(serde_json::from_str(line)?)
+    }
+}
+
+pub(crate) fn apply_deltas(local: &mut [Column], deltas: &[Delta]) -> Result<(), ClientError> {
+    for delta in deltas {
+        let (Delta::HiddenCard { index }
+        | Delta::AppendCard { index, .. }
+        | Delta::PopCard { index }) = delta;
+        if *index >= local.len() {
+            return Err(ClientError::InvalidFrame {
+                reason: format!("column {} of {}", index, local.len()),
+            });
+        }
+        match delta {
+            Delta::HiddenCard { index: i } => local[*i].push(None),
+            Delta::AppendCard { index: i, card: c } => local[*i].push(Some(*c)),
+            Delta::PopCard { index: i } => {
+                local[*i].pop();
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    // Shared is a writer the test can read back
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn spectator_tracks_client() {
+        let mut client = Client::from_hex("v1-0123456789abcdef0123456789abcdef").unwrap();
+        client.deal().unwrap();
+        let out = Shared::default();
+        client.broadcast(Box::new(out.clone())).unwrap();
+        let m = client.possible_moves().unwrap()[0].mv;
+        client.move_cards(m).unwrap();
+        client.deal().unwrap();
+        client.undo().unwrap();
+        client.end_broadcast().unwrap();
+
+        let text = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        let mut spectator = Spectator::default();
+        for line in text.lines() {
+            assert!(!spectator.over);
+            spectator.read_line(line).unwrap();
+        }
+        assert_eq!(text.lines().count(), 5);
+        assert!(spectator.over);
+        assert_eq!(spectator.local, client.local);
+        assert_eq!(spectator.seed, client.seed());
+        assert_eq!(spectator.status.moves, 1);
+        assert_eq!(spectator.status.score, client.score());
+
+        // a frame that doesn't fit the columns is refused, not applied
+        for line in [
+            r#"{"frame":"update","deltas":[{"PopCard":{"index":10}}],"status":{"score":0,"moves":0,"completed_runs":0,"cards_remaining":0,"won":false}}"#,
+            r#"{"frame":"start","variant":"spider","seed":"x","width":1000000,"deltas":[],"status":{"score":0,"moves":0,"completed_runs":0,"cards_remaining":0,"won":false}}"#,
+            "not json",
+        ] {
+            assert!(spectator.read_line(line).is_err(), "{}", line);
+        }
+        assert_eq!(spectator.local, client.local);
+    }
+}
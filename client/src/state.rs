@@ -0,0 +1,89 @@
+// State is everything a player can see of the game, for tools that
+// want it as JSON rather than as the player's text display.
+// Hidden cards are null: the state never gives away the cards.
+
+use serde::{Deserialize, Serialize};
+
+use super::error::ClientError;
+use super::session::SessionStats;
+use super::{Client, PotentialMove};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct State {
+    pub seed: String,
+    pub variant: String,
+    pub empty_columns: game::rules::EmptyColumns,
+    pub undo_mode: game::undo::UndoMode,
+    pub digest: String,
+    // the columns as the player sees them, bottom card first
+    pub columns: Vec<Vec<Option<cards::Card>>>,
+    pub cards_dealt: usize,
+    pub cards_remaining: usize,
+    // the suits of the completed runs, in order of completion
+    pub completed: Vec<cards::suit::Suit>,
+    pub moves: Vec<PotentialMove>,
+    pub checkpoints: Vec<game::Checkpoint>,
+    pub status: Status,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Status {
+    pub score: i64,
+    pub won: bool,
+    pub session: SessionStats,
+}
+
+impl Client {
+    pub fn state(&self) -> Result<State, ClientError> {
+        Ok(State {
+            seed: self.seed(),
+            variant: self.layout().name.clone(),
+            empty_columns: self.layout().empty_columns,
+            undo_mode: self.undo_mode(),
+            digest: self.digest(),
+            columns: self.local.clone(),
+            cards_dealt: self.cards_dealt(),
+            cards_remaining: self.total_cards() - self.cards_dealt(),
+            completed: self.remote.position().completed,
+            moves: self.possible_moves()?,
+            checkpoints: self.checkpoints(),
+            status: Status {
+                score: self.score(),
+                won: self.is_won(),
+                session: self.session_stats(),
+            },
+        })
+    }
+
+    // to_json writes the state on a single line
+    pub fn to_json(&self) -> Result<String, ClientError> {
+        Ok(serde_json::to_string(&self.state()?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_round_trip() {
+        let mut client = Client::from_hex("v1-0123456789abcdef0123456789abcdef").unwrap();
+        client.deal().unwrap();
+
+        let json = client.to_json().unwrap();
+        assert!(!json.contains('\n'));
+        let state: State = serde_json::from_str(&json).unwrap();
+        assert_eq!(state.seed, client.seed());
+        assert_eq!(state.digest, client.digest());
+        assert_eq!(state.columns, client.local);
+        assert_eq!(state.cards_remaining, 40);
+        assert_eq!(state.moves.len(), client.possible_moves().unwrap().len());
+        assert_eq!(state.checkpoints.len(), 2);
+        assert_eq!(state.status.session.deals, 1);
+
+        // hidden cards stay hidden
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(value["columns"][0][0].is_null());
+        assert_eq!(value["columns"][0].as_array().unwrap().len(), 7);
+    }
+}
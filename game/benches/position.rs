@@ -0,0 +1,85 @@
+// compare the Game position structure with the packed representation
+// on the operations a solver repeats most: cloning and making moves
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use game::packed::Packed;
+use game::{Game, Move};
+
+fn game() -> Game {
+    let mut game = Game::from_seed("v1-0123456789abcdef0123456789abcdef".parse().unwrap()).unwrap();
+    game.deal().unwrap();
+    game
+}
+
+// first_move finds a valid move so both representations do the same work
+fn first_move(game: &Game) -> Move {
+    for orig_col in 0..10 {
+        for dest_col in 0..10 {
+            let m = Move {
+                orig_col,
+                count: 1,
+                dest_col,
+            };
+            if game.is_move_valid(&m) {
+                return m;
+            }
+        }
+    }
+    panic!("no valid move in the benchmark position");
+}
+
+fn clone(c: &mut Criterion) {
+    let game = game();
+    let columns = game.position().columns;
+    let packed = Packed::from_game(&game).unwrap();
+
+    let mut group = c.benchmark_group("clone");
+    group.bench_function("columns", |b| b.iter(|| black_box(&columns).clone()));
+    group.bench_function("packed", |b| b.iter(|| *black_box(&packed)));
+    group.finish();
+}
+
+fn make_unmake(c: &mut Criterion) {
+    let mut game = game();
+    let m = first_move(&game);
+    let mut packed = Packed::from_game(&game).unwrap();
+
+    let mut group = c.benchmark_group("make_unmake");
+    group.bench_function("game", |b| {
+        b.iter(|| {
+            game.move_cards(black_box(m)).unwrap();
+            game.undo().unwrap();
+        })
+    });
+    group.bench_function("packed", |b| {
+        b.iter(|| {
+            let undo = packed.make_move(&game::rules::Spider, black_box(&m));
+            packed.unmake_move(&m, undo);
+        })
+    });
+    group.finish();
+}
+
+fn deal_undeal(c: &mut Criterion) {
+    let mut game = game();
+    let mut packed = Packed::from_game(&game).unwrap();
+
+    let mut group = c.benchmark_group("deal_undeal");
+    group.bench_function("game", |b| {
+        b.iter(|| {
+            game.deal().unwrap();
+            game.undo().unwrap();
+        })
+    });
+    group.bench_function("packed", |b| {
+        b.iter(|| {
+            let undo = packed.deal(black_box(&game::rules::Spider)).unwrap();
+            packed.undeal(undo);
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, clone, make_unmake, deal_undeal);
+criterion_main!(benches);
@@ -0,0 +1,77 @@
+// deck reads and writes a deck order as text.
+//
+// Each card is written as a rank character followed by a suit character,
+// using the same codes as the cards crate byte conversions: "QH", "XS"
+// (ten of spades). Any notation the cards crate parses is accepted on input.
+// Cards are separated by whitespace and dealt in the order written.
+// Everything after a '#' on a line is a comment.
+
+use super::error::GameError;
+use super::source;
+
+const CARDS_PER_LINE: usize = 13;
+
+// parse reads a deck and checks that it is a complete deck
+pub fn parse(text: &str) -> Result<Vec<cards::Card>, GameError> {
+    let mut deck: Vec<cards::Card> = Vec::new();
+    for line in text.lines() {
+        let line = match line.find('#') {
+            Some(i) => &line[..i],
+            None => line,
+        };
+        for token in line.split_whitespace() {
+            deck.push(parse_card(token)?);
+        }
+    }
+    source::validate_deck(&deck)?;
+
+    Ok(deck)
+}
+
+pub fn format(deck: &[cards::Card]) -> String {
+    let mut text = String::new();
+    for line in deck.chunks(CARDS_PER_LINE) {
+        let tokens: Vec<String> = line.iter().map(|c| format_card(*c)).collect();
+        text.push_str(&tokens.join(" "));
+        text.push('\n');
+    }
+    text
+}
+
+pub fn parse_card(token: &str) -> Result<cards::Card, GameError> {
+    Ok(token.parse::<cards::Card>()?)
+}
+
+pub fn format_card(card: cards::Card) -> String {
+    let bytes = [u8::from(card.rank), u8::from(card.suit)];
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let deck = source::standard_deck();
+        let text = format(&deck);
+        assert_eq!(text.lines().count(), 8);
+        assert_eq!(parse(&text).unwrap(), deck);
+    }
+
+    #[test]
+    fn invalid_decks() {
+        let text = format(&source::standard_deck());
+        for (name, text) in [
+            ("bad card", text.replacen("AC", "1C", 1)),
+            ("missing card", text.replacen("AC", "", 1)),
+            ("duplicate card", text.replacen("AC", "2C", 1)),
+        ]
+        .iter()
+        {
+            assert!(parse(text).is_err(), "{}", name);
+        }
+        let commented = format!("# first line\n{}", text.replace('\n', " # trailing\n"));
+        assert!(parse(&commented).is_ok());
+    }
+}
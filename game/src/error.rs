@@ -13,6 +13,18 @@ pub enum GameError {
     #[error("no checkpoints to undo")]
     NoCheckpointsToUndo {},
 
+    #[error("undo is not allowed in this game")]
+    UndoNotAllowed {},
+
+    #[error("no undos left: the limit is {}", limit)]
+    UndoLimitReached { limit: usize },
+
+    #[error(
+        "unknown undo mode {}: expected unlimited, limit:<n>, cost:<n> or none",
+        name
+    )]
+    UnknownUndoMode { name: String },
+
     #[error("unknown checkpoint")]
     UnknownCheckpoint {},
 
@@ -21,4 +33,27 @@ pub enum GameError {
 
     #[error("rewind into the future")]
     RewindIntoFuture {},
+
+    #[error("unknown seed version {}", version)]
+    UnknownSeedVersion { version: String },
+
+    #[error("invalid position: {}", reason)]
+    InvalidPosition { reason: String },
+
+    #[error("unknown variant {}", name)]
+    UnknownVariant { name: String },
+
+    #[error("unknown empty column rule {}: expected strict, fill or skip", name)]
+    UnknownEmptyColumns { name: String },
+
+    #[error("invalid layout {}: {}", name, reason)]
+    InvalidLayout { name: String, reason: String },
+
+    /// Represents CardError
+    #[error(transparent)]
+    CardError(#[from] cards::error::CardError),
+
+    /// Represents HexError
+    #[error(transparent)]
+    HexError(#[from] hex::FromHexError),
 }
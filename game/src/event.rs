@@ -0,0 +1,73 @@
+// events tell observers what a move, deal or undo did, so logging,
+// statistics, sound and display can follow the game without the game
+// knowing about them. Events only name cards that are face up.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::{Checkpoint, Move};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Event {
+    CardsMoved {
+        mv: Move,
+    },
+    // a hidden card at the top of a column turned face up
+    CardFlipped {
+        index: usize,
+        card: cards::Card,
+    },
+    // the cards dealt, one for each column in column order
+    Dealt {
+        cards: Vec<cards::Card>,
+    },
+    RunCompleted {
+        index: usize,
+        suit: cards::suit::Suit,
+    },
+    // the checkpoint that was undone
+    Undone {
+        checkpoint: Checkpoint,
+    },
+    Won,
+}
+
+pub trait Observer {
+    fn notify(&mut self, event: &Event);
+}
+
+// any closure taking an event is an observer
+impl<F: FnMut(&Event)> Observer for F {
+    fn notify(&mut self, event: &Event) {
+        self(event)
+    }
+}
+
+#[derive(Default)]
+pub struct Observers(Vec<Box<dyn Observer>>);
+
+impl Observers {
+    pub fn subscribe(&mut self, observer: Box<dyn Observer>) {
+        self.0.push(observer);
+    }
+
+    pub fn publish(&mut self, event: &Event) {
+        for observer in self.0.iter_mut() {
+            observer.notify(event);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Observers({})", self.0.len())
+    }
+}
@@ -0,0 +1,281 @@
+// layout describes a variant of Spider: the cards in play, the number of
+// columns, how many cards each column starts with and the rules of play,
+// including the house rule for dealing while a column is empty.
+// Unless the layout says otherwise, the top card of each column starts
+// face up and the rest face down. Each deal from the stock puts one card
+// on every column, from the first, until the stock runs out.
+//
+// Layouts are plain data, so variants beyond the built in ones can come
+// from configuration.
+
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use cards::suit::Suit;
+
+use super::error::GameError;
+use super::rules::{EmptyColumns, RuleSet};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Layout {
+    pub name: String,
+    // the cards are this many copies of the thirteen cards of each suit
+    pub copies: usize,
+    pub suits: Vec<Suit>,
+    // the number of cards dealt to each column at the start
+    pub columns: Vec<usize>,
+    // the number of those cards dealt face down, if not all but the top one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub face_down: Option<Vec<usize>>,
+    // the built in rules the layout is played by; a game given rules of
+    // its own ignores them
+    #[serde(default)]
+    pub rules: RuleSet,
+    #[serde(default)]
+    pub empty_columns: EmptyColumns,
+}
+
+const ALL_SUITS: [Suit; 4] = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+
+impl Layout {
+    // spider is the standard game: two decks, ten columns
+    pub fn spider() -> Layout {
+        Layout::spider_suits("spider", 2, &ALL_SUITS)
+    }
+
+    pub fn spider_two_suits() -> Layout {
+        Layout::spider_suits("spider-2-suit", 4, &[Suit::Hearts, Suit::Spades])
+    }
+
+    pub fn spider_one_suit() -> Layout {
+        Layout::spider_suits("spider-1-suit", 8, &[Suit::Spades])
+    }
+
+    fn spider_suits(name: &str, copies: usize, suits: &[Suit]) -> Layout {
+        Layout {
+            name: name.to_string(),
+            copies,
+            suits: suits.to_vec(),
+            columns: vec![6, 5, 5, 6, 5, 5, 6, 5, 5, 6],
+            face_down: None,
+            rules: RuleSet::Spider,
+            empty_columns: EmptyColumns::Strict,
+        }
+    }
+
+    // spider8 plays the two decks on eight columns
+    pub fn spider8() -> Layout {
+        Layout {
+            name: "spider8".to_string(),
+            copies: 2,
+            suits: ALL_SUITS.to_vec(),
+            columns: vec![6; 8],
+            face_down: None,
+            rules: RuleSet::Spider,
+            empty_columns: EmptyColumns::Strict,
+        }
+    }
+
+    // spiderette is one deck on seven columns, laid out as in Klondike
+    pub fn spiderette() -> Layout {
+        Layout {
+            name: "spiderette".to_string(),
+            copies: 1,
+            suits: ALL_SUITS.to_vec(),
+            columns: vec![1, 2, 3, 4, 5, 6, 7],
+            face_down: None,
+            rules: RuleSet::Spider,
+            empty_columns: EmptyColumns::Strict,
+        }
+    }
+
+    // will o' the wisp is spiderette with three cards in every column
+    pub fn will_o_the_wisp() -> Layout {
+        Layout {
+            name: "will-o-the-wisp".to_string(),
+            copies: 1,
+            suits: ALL_SUITS.to_vec(),
+            columns: vec![3; 7],
+            face_down: None,
+            rules: RuleSet::Spider,
+            empty_columns: EmptyColumns::Strict,
+        }
+    }
+
+    // scorpion is one deck on seven columns of seven, with three cards
+    // face down in each of the first four, and a stock of three cards
+    // dealt to the first three columns
+    pub fn scorpion() -> Layout {
+        Layout {
+            name: "scorpion".to_string(),
+            copies: 1,
+            suits: ALL_SUITS.to_vec(),
+            columns: vec![7; 7],
+            face_down: Some(vec![3, 3, 3, 3, 0, 0, 0]),
+            rules: RuleSet::Scorpion,
+            empty_columns: EmptyColumns::Strict,
+        }
+    }
+
+    // builtin lists the variants that need no configuration
+    pub fn builtin() -> Vec<Layout> {
+        vec![
+            Layout::spider(),
+            Layout::spider_two_suits(),
+            Layout::spider_one_suit(),
+            Layout::spider8(),
+            Layout::spiderette(),
+            Layout::will_o_the_wisp(),
+            Layout::scorpion(),
+        ]
+    }
+
+    pub fn width(&self) -> usize {
+        self.columns.len()
+    }
+
+    // face_down is the number of cards dealt face down to column i
+    pub fn face_down(&self, i: usize) -> usize {
+        match &self.face_down {
+            Some(face_down) => face_down[i],
+            None => self.columns[i] - 1,
+        }
+    }
+
+    pub fn deck(&self) -> cards::deck::Deck {
+        cards::deck::Deck::with_suits(self.copies, &self.suits)
+    }
+
+    pub fn total_cards(&self) -> usize {
+        self.copies * self.suits.len() * super::RUN_LENGTH
+    }
+
+    // initial_cards is the number of cards laid out before the first deal
+    pub fn initial_cards(&self) -> usize {
+        self.columns.iter().sum()
+    }
+
+    // validate checks that the layout can be dealt and played
+    pub fn validate(&self) -> Result<(), GameError> {
+        let invalid = |reason: String| {
+            Err(GameError::InvalidLayout {
+                name: self.name.clone(),
+                reason,
+            })
+        };
+        if self.columns.is_empty() || self.width() > super::packed::MAX_COLUMNS {
+            return invalid(format!(
+                "{} columns, expected 1 to {}",
+                self.width(),
+                super::packed::MAX_COLUMNS
+            ));
+        }
+        if self.columns.contains(&0) {
+            return invalid("every column needs at least one card".to_string());
+        }
+        if let Some(face_down) = &self.face_down {
+            if face_down.len() != self.width() {
+                return invalid(format!(
+                    "face down cards for {} columns, expected {}",
+                    face_down.len(),
+                    self.width()
+                ));
+            }
+            if face_down.iter().zip(&self.columns).any(|(f, c)| f >= c) {
+                return invalid("every column needs a face up card".to_string());
+            }
+        }
+        if self.suits.is_empty() || self.copies == 0 {
+            return invalid("no cards".to_string());
+        }
+        if self.total_cards() > super::packed::MAX_CARDS {
+            return invalid(format!(
+                "{} cards, expected at most {}",
+                self.total_cards(),
+                super::packed::MAX_CARDS
+            ));
+        }
+        if self.initial_cards() > self.total_cards() {
+            return invalid(format!(
+                "{} cards in the columns, but only {} cards",
+                self.initial_cards(),
+                self.total_cards()
+            ));
+        }
+        Ok(())
+    }
+
+    // validate_deck checks that the cards are exactly this layout's cards
+    // in some order
+    pub fn validate_deck(&self, cards: &[cards::Card]) -> Result<(), GameError> {
+        Ok(self.deck().verify(cards)?)
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Layout {
+        Layout::spider()
+    }
+}
+
+impl FromStr for Layout {
+    type Err = GameError;
+
+    // from_str finds a builtin variant by name
+    fn from_str(s: &str) -> Result<Layout, GameError> {
+        Layout::builtin()
+            .into_iter()
+            .find(|layout| layout.name == s)
+            .ok_or_else(|| GameError::UnknownVariant {
+                name: s.to_string(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin() {
+        for layout in Layout::builtin() {
+            assert!(layout.validate().is_ok(), "{}", layout.name);
+            assert_eq!(layout.deck().len(), layout.total_cards(), "{}", layout.name);
+            assert_eq!(layout.name.parse::<Layout>().unwrap(), layout);
+        }
+        assert_eq!(Layout::spider().total_cards(), 104);
+        assert_eq!(Layout::spider().initial_cards(), 54);
+        assert_eq!(Layout::spiderette().total_cards(), 52);
+        assert_eq!(Layout::spiderette().initial_cards(), 28);
+        assert_eq!(Layout::scorpion().initial_cards(), 49);
+        assert_eq!(Layout::scorpion().face_down(3), 3);
+        assert_eq!(Layout::scorpion().face_down(4), 0);
+        assert_eq!(Layout::spider().face_down(0), 5);
+        assert!(matches!(
+            "klondike".parse::<Layout>(),
+            Err(GameError::UnknownVariant { .. })
+        ));
+    }
+
+    #[test]
+    fn invalid() {
+        let mut layout = Layout::spiderette();
+        layout.columns = vec![20; 7];
+        assert!(layout.validate().is_err());
+        layout.columns = vec![3; 11];
+        assert!(layout.validate().is_err());
+        layout.columns = vec![0, 3];
+        assert!(layout.validate().is_err());
+        layout.columns = vec![3, 3];
+        layout.copies = 2;
+        assert!(layout.validate().is_ok());
+        layout.face_down = Some(vec![2, 3]);
+        assert!(layout.validate().is_err(), "no face up card");
+        layout.face_down = Some(vec![2]);
+        assert!(layout.validate().is_err());
+        layout.face_down = Some(vec![2, 0]);
+        assert!(layout.validate().is_ok());
+        layout.copies = 3;
+        assert!(layout.validate().is_err(), "too many cards to pack");
+    }
+}
@@ -2,27 +2,41 @@
 
 use self::delta::Delta;
 use error::GameError;
+use serde::{Deserialize, Serialize};
 
+pub mod deck;
 pub mod delta;
 pub mod error;
+pub mod event;
+pub mod layout;
+pub mod packed;
+pub mod position;
+pub mod rules;
 pub mod seed;
+pub mod shuffle;
+pub mod solver;
 pub mod source;
+pub mod undo;
 
 //use error::GameError;
 
-#[derive(Debug, PartialOrd, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialOrd, PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub enum ColumnCard {
     Visible { card: cards::Card },
     Hidden { card: cards::Card },
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Checkpoint {
     Start {
         count: usize,
     },
+    // columns are the columns dealt to, in column order, and
+    // completions are the runs the deal completed, in column order
     Deal {
         count: usize,
+        columns: Vec<usize>,
+        completions: Vec<Completion>,
     },
     // flipped_hidden_card means there was a hidden card left in the origin column
     // to make  the move we had to flip the card
@@ -30,69 +44,193 @@ pub enum Checkpoint {
     Move {
         action: Move,
         flipped_hidden_card: bool,
+        completion: Option<Completion>,
     },
 }
 
+// Completion records a King to Ace run removed from a column.
+// flipped_hidden_card means removing the run exposed a hidden card,
+// which was flipped; to undo the completion, we must flip it back
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct Completion {
+    pub index: usize,
+    pub suit: cards::suit::Suit,
+    pub flipped_hidden_card: bool,
+}
+
 #[derive(Debug)]
 pub struct Game {
+    layout: layout::Layout,
+    rules: Box<dyn rules::Rules>,
     source: source::Source,
     columns: Vec<Vec<ColumnCard>>,
+    completed: Vec<cards::suit::Suit>,
     checkpoints: Vec<Checkpoint>,
+    undo_mode: undo::UndoMode,
+    // the number of undos made, for the undo mode's limit
+    undos: usize,
+    observers: event::Observers,
 }
 
-#[derive(Debug, PartialOrd, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialOrd, PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub struct Move {
     pub orig_col: usize,
     pub count: usize,
     pub dest_col: usize,
 }
 
-const WIDTH: usize = 10;
-
-fn initial_counts() -> [usize; WIDTH] {
-    [6, 5, 5, 6, 5, 5, 6, 5, 5, 6]
-}
+const RUN_LENGTH: usize = 13;
 
 impl Game {
-    // create a new game from a randomly generated seed
+    // create a new game of Spider from a randomly generated seed
     pub fn new() -> Result<Game, GameError> {
-        Game::from_source(source::Source::new())
+        Game::from_seed(seed::from_random())
+    }
+
+    // create a new game of Spider from a specified seed
+    pub fn from_seed(seed: seed::Seed) -> Result<Game, GameError> {
+        Game::from_layout_seed(layout::Layout::spider(), seed)
+    }
+
+    // create a new game of Spider dealing from an explicit deck order
+    pub fn from_deck(deck: Vec<cards::Card>) -> Result<Game, GameError> {
+        Game::from_layout_deck(layout::Layout::spider(), deck)
     }
 
-    // create a new game from a specified seed
-    pub fn from_seed(seed: [u8; 16]) -> Result<Game, GameError> {
-        Game::from_source(source::Source::from_seed(seed))
+    // create a game of Spider from a position in progress
+    pub fn from_position(position: position::Position) -> Result<Game, GameError> {
+        Game::from_layout_position(layout::Layout::spider(), position)
     }
 
-    fn from_source(mut source: source::Source) -> Result<Game, GameError> {
+    // create a new game of any variant from a specified seed
+    pub fn from_layout_seed(layout: layout::Layout, seed: seed::Seed) -> Result<Game, GameError> {
+        layout.validate()?;
+        let rules = rules::for_layout(&layout);
+        let source = source::Source::from_seed(&layout, seed);
+        Game::from_source(layout, rules, source)
+    }
+
+    // create a new game of the layout played by rules of its own
+    pub fn from_rules_seed(
+        layout: layout::Layout,
+        rules: Box<dyn rules::Rules>,
+        seed: seed::Seed,
+    ) -> Result<Game, GameError> {
+        layout.validate()?;
+        let source = source::Source::from_seed(&layout, seed);
+        Game::from_source(layout, rules, source)
+    }
+
+    pub fn from_layout_deck(
+        layout: layout::Layout,
+        deck: Vec<cards::Card>,
+    ) -> Result<Game, GameError> {
+        layout.validate()?;
+        let rules = rules::for_layout(&layout);
+        let source = source::Source::from_deck(&layout, deck)?;
+        Game::from_source(layout, rules, source)
+    }
+
+    // the position becomes the starting point, so there is nothing to undo
+    pub fn from_layout_position(
+        layout: layout::Layout,
+        position: position::Position,
+    ) -> Result<Game, GameError> {
+        layout.validate()?;
+        position.validate(&layout)?;
+        // the cards in the columns and completed runs count as dealt
+        let on_table: usize = position.columns.iter().map(Vec::len).sum();
+        let mut dealt = position.cards();
+        dealt.drain(on_table..on_table + position.stock.len());
+        let source = source::Source::from_stock(dealt, position.stock);
+        Ok(Game {
+            rules: rules::for_layout(&layout),
+            layout,
+            checkpoints: vec![Checkpoint::Start {
+                count: source.cards_dealt(),
+            }],
+            source,
+            columns: position.columns,
+            completed: position.completed,
+            undo_mode: undo::UndoMode::default(),
+            undos: 0,
+            observers: event::Observers::default(),
+        })
+    }
+
+    fn from_source(
+        layout: layout::Layout,
+        rules: Box<dyn rules::Rules>,
+        mut source: source::Source,
+    ) -> Result<Game, GameError> {
         let checkpoint_count = source.cards_dealt();
 
         let mut columns: Vec<Vec<ColumnCard>> = Vec::new();
-        for c in initial_counts().iter() {
+        for (i, c) in layout.columns.iter().enumerate() {
             let mut column: Vec<ColumnCard> = Vec::new();
-            for _ in 0..*c - 1 {
+            for _ in 0..layout.face_down(i) {
                 let wrapped_card = ColumnCard::Hidden {
                     card: source.deal()?,
                 };
                 column.push(wrapped_card);
             }
-            let wrapped_card = ColumnCard::Visible {
-                card: source.deal()?,
-            };
-            column.push(wrapped_card);
+            for _ in layout.face_down(i)..*c {
+                let wrapped_card = ColumnCard::Visible {
+                    card: source.deal()?,
+                };
+                column.push(wrapped_card);
+            }
             columns.push(column);
         }
 
         Ok(Game {
+            layout,
+            rules,
             source,
             columns,
+            completed: Vec::new(),
             checkpoints: vec![Checkpoint::Start {
                 count: checkpoint_count,
             }],
+            undo_mode: undo::UndoMode::default(),
+            undos: 0,
+            observers: event::Observers::default(),
         })
     }
 
-    pub fn seed(&self) -> [u8; 16] {
+    // with_undo_mode sets how often the new game may be undone
+    pub fn with_undo_mode(mut self, undo_mode: undo::UndoMode) -> Game {
+        self.undo_mode = undo_mode;
+        self
+    }
+
+    pub fn undo_mode(&self) -> undo::UndoMode {
+        self.undo_mode
+    }
+
+    pub fn undos(&self) -> usize {
+        self.undos
+    }
+
+    // subscribe registers an observer for the events of every move,
+    // deal and undo from now on
+    pub fn subscribe(&mut self, observer: Box<dyn event::Observer>) {
+        self.observers.subscribe(observer);
+    }
+
+    pub fn layout(&self) -> &layout::Layout {
+        &self.layout
+    }
+
+    pub fn rules(&self) -> &dyn rules::Rules {
+        self.rules.as_ref()
+    }
+
+    pub fn width(&self) -> usize {
+        self.layout.width()
+    }
+
+    pub fn seed(&self) -> Option<seed::Seed> {
         self.source.seed()
     }
 
@@ -103,14 +241,32 @@ impl Game {
         self.source.cards_dealt()
     }
 
+    pub fn position(&self) -> position::Position {
+        position::Position {
+            columns: self.columns.clone(),
+            stock: self.source.remaining(),
+            completed: self.completed.clone(),
+        }
+    }
+
     pub fn checkpoints(&self) -> Vec<Checkpoint> {
         self.checkpoints.to_vec()
     }
 
+    pub fn completed_runs(&self) -> usize {
+        self.completed.len()
+    }
+
+    // the game is won when every card has gone into a completed run
+    pub fn is_won(&self) -> bool {
+        self.source.cards_dealt() == self.source.total_cards()
+            && self.columns.iter().all(|column| column.is_empty())
+    }
+
     pub fn initial_deltas(&self) -> Vec<delta::Delta> {
         use self::delta::Delta::*;
         let mut deltas: Vec<delta::Delta> = Vec::new();
-        for i in 0..WIDTH {
+        for i in 0..self.width() {
             for column_card in self.columns[i].iter() {
                 match column_card {
                     ColumnCard::Hidden { .. } => deltas.push(HiddenCard { index: i }),
@@ -127,23 +283,47 @@ impl Game {
         let mut deltas: Vec<delta::Delta> = Vec::new();
         let checkpoint_count = self.source.cards_dealt();
 
-        // check all the columns first, then we don't have to revert
-        // anything on error
-        for i in 0..WIDTH {
-            if self.columns[i].is_empty() {
-                return Err(GameError::DealToEmptyColumn {});
-            }
+        // check the stock and the rules first, then we don't have to
+        // revert anything on error
+        let remaining = self.source.total_cards() - checkpoint_count;
+        if remaining == 0 {
+            return Err(GameError::DealFromEmptyDeck {});
+        }
+        let mut lens = [0; packed::MAX_COLUMNS];
+        for (len, column) in lens.iter_mut().zip(&self.columns) {
+            *len = column.len() as u8;
         }
+        let dealt = self.rules.deal_columns(&lens[..self.width()], remaining)?;
+        let columns: Vec<usize> = (0..self.width())
+            .filter(|i| dealt & (1 << i) != 0)
+            .collect();
 
-        for i in 0..WIDTH {
+        let mut dealt: Vec<cards::Card> = Vec::new();
+        for &i in columns.iter() {
             let card = self.source.deal()?;
             self.columns[i].push(ColumnCard::Visible { card });
             deltas.push(Delta::AppendCard { index: i, card });
+            dealt.push(card);
         }
 
+        let mut completions: Vec<Completion> = Vec::new();
+        for i in 0..self.width() {
+            if let Some(completion) = self.complete_run(i, &mut deltas) {
+                completions.push(completion);
+            }
+        }
+
+        self.observers
+            .publish(&event::Event::Dealt { cards: dealt });
+        for completion in &completions {
+            self.publish_completion(completion);
+        }
         self.checkpoints.push(Checkpoint::Deal {
             count: checkpoint_count,
+            columns,
+            completions,
         });
+        self.publish_won();
 
         Ok(deltas)
     }
@@ -170,29 +350,22 @@ impl Game {
             }
         }
 
-        // if the origin column now ends with a hidden card,
-        // flip it to visible
-        let flipped_hidden_card = if self.columns[m.orig_col].is_empty() {
-            false
-        } else {
-            let last_card_index = self.columns[m.orig_col].len() - 1;
-            if let ColumnCard::Hidden { card: c } = self.columns[m.orig_col][last_card_index] {
-                self.columns[m.orig_col][last_card_index] = ColumnCard::Visible { card: c };
-                deltas.push(delta::Delta::PopCard { index: m.orig_col });
-                deltas.push(delta::Delta::AppendCard {
-                    index: m.orig_col,
-                    card: c,
-                });
-                true
-            } else {
-                false
-            }
-        };
+        let flipped_hidden_card = self.flip_last_card(m.orig_col, &mut deltas);
+        let completion = self.complete_run(m.dest_col, &mut deltas);
 
+        self.observers.publish(&event::Event::CardsMoved { mv: m });
+        if flipped_hidden_card {
+            self.publish_flip(m.orig_col);
+        }
+        if let Some(completion) = &completion {
+            self.publish_completion(completion);
+        }
         self.checkpoints.push(Checkpoint::Move {
             action: m,
             flipped_hidden_card,
+            completion,
         });
+        self.publish_won();
 
         Ok(deltas)
     }
@@ -208,12 +381,7 @@ impl Game {
 
         // if we flipped the top card, flip it back
         if flipped_hidden_card {
-            let last_card_index = self.columns[m.orig_col].len() - 1;
-            if let ColumnCard::Visible { card: c } = self.columns[m.orig_col][last_card_index] {
-                self.columns[m.orig_col][last_card_index] = ColumnCard::Hidden { card: c };
-                deltas.push(delta::Delta::PopCard { index: m.orig_col });
-                deltas.push(delta::Delta::HiddenCard { index: m.orig_col });
-            }
+            self.unflip_last_card(m.orig_col, &mut deltas);
         }
 
         let dest_len = self.columns[m.dest_col].len();
@@ -235,31 +403,152 @@ impl Game {
     }
 
     pub fn undo(&mut self) -> Result<Vec<delta::Delta>, GameError> {
+        self.undo_mode.check(self.undos)?;
         if self.checkpoints.len() < 2 {
             return Err(GameError::NoCheckpointsToUndo {});
         };
 
-        match self.checkpoints.pop() {
-            Some(Checkpoint::Deal { count }) => {
+        let deltas = match self.checkpoints.last().cloned() {
+            Some(Checkpoint::Deal {
+                count,
+                columns,
+                completions,
+            }) => {
                 let mut deltas: Vec<delta::Delta> = Vec::new();
                 self.source.rewind(count)?;
-                for i in 0..WIDTH {
-                    self.columns[1].pop();
+                for completion in completions.iter().rev() {
+                    self.restore_run(completion, &mut deltas);
+                }
+                for i in columns {
+                    self.columns[i].pop();
                     deltas.push(Delta::PopCard { index: i });
                 }
-                Ok(deltas)
+                deltas
             }
             Some(Checkpoint::Move {
                 action,
                 flipped_hidden_card,
-            }) => self.reverse_move_cards(action, flipped_hidden_card),
-            _unknown => Err(GameError::UnknownCheckpoint {}),
+                completion,
+            }) => {
+                let mut deltas: Vec<delta::Delta> = Vec::new();
+                if let Some(completion) = completion {
+                    self.restore_run(&completion, &mut deltas);
+                }
+                deltas.extend(self.reverse_move_cards(action, flipped_hidden_card)?);
+                deltas
+            }
+            _unknown => return Err(GameError::UnknownCheckpoint {}),
+        };
+
+        self.undos += 1;
+        if let Some(checkpoint) = self.checkpoints.pop() {
+            self.observers.publish(&event::Event::Undone { checkpoint });
+        }
+        Ok(deltas)
+    }
+
+    fn publish_flip(&mut self, index: usize) {
+        if let Some(ColumnCard::Visible { card }) = self.columns[index].last().copied() {
+            self.observers
+                .publish(&event::Event::CardFlipped { index, card });
+        }
+    }
+
+    fn publish_completion(&mut self, completion: &Completion) {
+        self.observers.publish(&event::Event::RunCompleted {
+            index: completion.index,
+            suit: completion.suit,
+        });
+        if completion.flipped_hidden_card {
+            self.publish_flip(completion.index);
+        }
+    }
+
+    fn publish_won(&mut self) {
+        if self.is_won() {
+            self.observers.publish(&event::Event::Won);
+        }
+    }
+
+    // if the column now ends with a hidden card, flip it to visible
+    fn flip_last_card(&mut self, index: usize, deltas: &mut Vec<delta::Delta>) -> bool {
+        match self.columns[index].last().copied() {
+            Some(ColumnCard::Hidden { card: c }) => {
+                let last_card_index = self.columns[index].len() - 1;
+                self.columns[index][last_card_index] = ColumnCard::Visible { card: c };
+                deltas.push(delta::Delta::PopCard { index });
+                deltas.push(delta::Delta::AppendCard { index, card: c });
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn unflip_last_card(&mut self, index: usize, deltas: &mut Vec<delta::Delta>) {
+        if let Some(ColumnCard::Visible { card: c }) = self.columns[index].last().copied() {
+            let last_card_index = self.columns[index].len() - 1;
+            self.columns[index][last_card_index] = ColumnCard::Hidden { card: c };
+            deltas.push(delta::Delta::PopCard { index });
+            deltas.push(delta::Delta::HiddenCard { index });
+        }
+    }
+
+    // if the column ends with a visible run the rules say is complete,
+    // remove it
+    fn complete_run(&mut self, index: usize, deltas: &mut Vec<delta::Delta>) -> Option<Completion> {
+        let column = &self.columns[index];
+        if column.len() < RUN_LENGTH {
+            return None;
+        }
+        let mut run = Vec::<cards::Card>::new();
+        for column_card in column.iter().skip(column.len() - RUN_LENGTH) {
+            match column_card {
+                ColumnCard::Hidden { .. } => return None,
+                ColumnCard::Visible { card: c } => run.push(*c),
+            }
+        }
+        if !self.rules.is_complete_run(&run) {
+            return None;
+        }
+
+        let suit = run[0].suit;
+        let len = self.columns[index].len();
+        self.columns[index].truncate(len - RUN_LENGTH);
+        for _ in 0..RUN_LENGTH {
+            deltas.push(delta::Delta::PopCard { index });
+        }
+        self.completed.push(suit);
+        let flipped_hidden_card = self.flip_last_card(index, deltas);
+
+        Some(Completion {
+            index,
+            suit,
+            flipped_hidden_card,
+        })
+    }
+
+    // restore_run puts a completed run back in its column
+    fn restore_run(&mut self, completion: &Completion, deltas: &mut Vec<delta::Delta>) {
+        if completion.flipped_hidden_card {
+            self.unflip_last_card(completion.index, deltas);
+        }
+        self.completed.pop();
+        for rank in cards::rank::iter().rev() {
+            let card = cards::Card {
+                suit: completion.suit,
+                rank,
+            };
+            self.columns[completion.index].push(ColumnCard::Visible { card });
+            deltas.push(delta::Delta::AppendCard {
+                index: completion.index,
+                card,
+            });
         }
     }
 
     pub fn is_move_valid(&self, m: &Move) -> bool {
-        if m.orig_col >= WIDTH
-            || m.dest_col >= WIDTH
+        if m.orig_col >= self.width()
+            || m.dest_col >= self.width()
             || m.orig_col == m.dest_col
             || self.columns[m.orig_col].is_empty()
             || m.count > self.columns[m.orig_col].len()
@@ -267,6 +556,7 @@ impl Game {
             false
         } else {
             is_move_valid(
+                self.rules.as_ref(),
                 &self.columns[m.orig_col],
                 m.count,
                 &self.columns[m.dest_col],
@@ -275,7 +565,12 @@ impl Game {
     }
 }
 
-fn is_move_valid(orig: &[ColumnCard], count: usize, dest: &[ColumnCard]) -> bool {
+fn is_move_valid(
+    rules: &dyn rules::Rules,
+    orig: &[ColumnCard],
+    count: usize,
+    dest: &[ColumnCard],
+) -> bool {
     let mut orig_cards = Vec::<cards::Card>::new();
     for c_card in orig.iter().skip(orig.len() - count) {
         match c_card {
@@ -283,22 +578,12 @@ fn is_move_valid(orig: &[ColumnCard], count: usize, dest: &[ColumnCard]) -> bool
             ColumnCard::Visible { card: c } => orig_cards.push(*c),
         };
     }
-    if cards::is_descending_run(orig_cards.as_slice()) {
-        match dest.last() {
-            // if the dest column is empty, any move is valid
-            None => true,
-            Some(dest_cc) => match dest_cc {
-                ColumnCard::Hidden { .. } => false,
-                ColumnCard::Visible { card: dest_card } => {
-                    match cards::rank::successor(orig_cards[0].rank) {
-                        None => false,
-                        Some(orig_rank_successor) => dest_card.rank == orig_rank_successor,
-                    }
-                }
-            },
+    match dest.last() {
+        None => rules.is_move_valid(&orig_cards, None),
+        Some(ColumnCard::Hidden { .. }) => false,
+        Some(ColumnCard::Visible { card: dest_card }) => {
+            rules.is_move_valid(&orig_cards, Some(*dest_card))
         }
-    } else {
-        false
     }
 }
 
@@ -306,6 +591,9 @@ fn is_move_valid(orig: &[ColumnCard], count: usize, dest: &[ColumnCard]) -> bool
 mod tests {
     use super::*;
 
+    const WIDTH: usize = 10;
+    use proptest::prelude::*;
+
     struct TestData {
         name: String,
         orig: Vec<ColumnCard>,
@@ -523,11 +811,514 @@ mod tests {
         ];
         for test_item in test_items {
             assert_eq!(
-                is_move_valid(&test_item.orig, test_item.count, &test_item.dest),
+                is_move_valid(
+                    &rules::Spider,
+                    &test_item.orig,
+                    test_item.count,
+                    &test_item.dest
+                ),
                 test_item.expected_result,
                 "{}",
                 test_item.name
             );
         }
     }
+
+    // completion_position lays out King to Two of spades over a hidden card
+    // in column 0 and puts an Ace of spades both on top of column 1 and
+    // first in the stock, so either a move or a deal completes the run
+    fn completion_position() -> position::Position {
+        use cards::rank::Rank::*;
+        use cards::suit::Suit::*;
+
+        let ace = cards::Card {
+            suit: Spades,
+            rank: Ace,
+        };
+        let mut rest = source::standard_deck();
+        let mut run = Vec::<ColumnCard>::new();
+        for rank in [
+            King, Queen, Jack, Ten, Nine, Eight, Seven, Six, Five, Four, Three, Two,
+        ]
+        .iter()
+        {
+            let card = cards::Card {
+                suit: Spades,
+                rank: *rank,
+            };
+            rest.remove(rest.iter().position(|c| *c == card).unwrap());
+            run.push(ColumnCard::Visible { card });
+        }
+        rest.retain(|c| *c != ace);
+
+        let mut stock = vec![ace];
+        stock.extend(rest.drain(..49));
+        let mut columns: Vec<Vec<ColumnCard>> = vec![Vec::new(); WIDTH];
+        columns[0].push(ColumnCard::Hidden {
+            card: rest.pop().unwrap(),
+        });
+        columns[0].extend(run);
+        columns[1].push(ColumnCard::Visible { card: ace });
+        for (i, card) in rest.into_iter().enumerate() {
+            columns[2 + i % (WIDTH - 2)].push(ColumnCard::Visible { card });
+        }
+
+        let position = position::Position {
+            columns,
+            stock,
+            completed: Vec::new(),
+        };
+        position.validate(&layout::Layout::spider()).unwrap();
+        position
+    }
+
+    #[test]
+    fn scorpion() {
+        let layout = layout::Layout::scorpion();
+        let game = Game::from_layout_seed(layout.clone(), seed::from_random()).unwrap();
+        let position = game.position();
+        assert_eq!(position.stock.len(), 3);
+        for (i, column) in position.columns.iter().enumerate() {
+            let hidden = column
+                .iter()
+                .filter(|c| matches!(c, ColumnCard::Hidden { .. }))
+                .count();
+            assert_eq!((column.len(), hidden), (7, layout.face_down(i)));
+        }
+
+        let position: position::Position = "\
+stock: 2C 3C 4C
+completed: S H
+column: [KD] QD 8C 5D
+column: 9C
+column: KC
+column:
+column: AC 5C 6C 7C XC JC QC
+column: AD 2D 3D 4D 6D 7D 8D 9D XD JD
+column:
+"
+        .parse()
+        .unwrap();
+        let mut game = Game::from_layout_position(layout, position).unwrap();
+        assert_eq!((game.cards_dealt(), game.total_cards()), (49, 52));
+        let mv = |orig_col, count, dest_col| Move {
+            orig_col,
+            count,
+            dest_col,
+        };
+        assert!(game.move_cards(mv(0, 2, 1)).is_ok(), "any cards, in suit");
+        assert!(game.move_cards(mv(0, 1, 3)).is_err(), "only Kings to empty");
+        assert!(game.move_cards(mv(2, 1, 3)).is_ok());
+        assert!(game.deal().is_ok(), "deals with columns empty");
+        let columns = game.position().columns;
+        assert_eq!(
+            columns[2],
+            vec![ColumnCard::Visible {
+                card: deck::parse_card("4C").unwrap()
+            }]
+        );
+        assert!(columns[6].is_empty());
+        assert!(game.deal().is_err());
+    }
+
+    #[test]
+    fn deal_with_empty_columns() {
+        use rules::EmptyColumns::*;
+
+        let position: position::Position = "\
+stock: 5S 6S 7S 8S 9S XS JS QS KS
+completed:
+column: [AS] [2S] 3S 4S
+column:
+"
+        .parse()
+        .unwrap();
+        let card = |text| deck::parse_card(text).unwrap();
+        let mut layout = layout::Layout {
+            name: "test".to_string(),
+            copies: 1,
+            suits: vec![cards::suit::Suit::Spades],
+            columns: vec![3, 3],
+            face_down: None,
+            rules: rules::RuleSet::Spider,
+            empty_columns: Strict,
+        };
+        for (empty_columns, dealt) in [(Strict, None), (Fill, Some(2)), (Skip, Some(1))] {
+            layout.empty_columns = empty_columns;
+            let mut game = Game::from_layout_position(layout.clone(), position.clone()).unwrap();
+            match dealt {
+                None => assert!(matches!(game.deal(), Err(GameError::DealToEmptyColumn {}))),
+                Some(dealt) => {
+                    game.deal().unwrap();
+                    let after = game.position();
+                    assert_eq!(after.stock.len(), 9 - dealt, "{}", empty_columns);
+                    assert_eq!(
+                        after.columns[0].last(),
+                        Some(&ColumnCard::Visible { card: card("5S") })
+                    );
+                    assert_eq!(after.columns[1].len(), dealt - 1);
+                    assert!(after.validate(&layout).is_ok());
+                    game.undo().unwrap();
+                    assert_eq!(game.position(), position);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn undo_deal() {
+        let mut game =
+            Game::from_seed("v1-0123456789abcdef0123456789abcdef".parse().unwrap()).unwrap();
+        let before = game.position();
+        let deltas = game.deal().unwrap();
+        assert_eq!(deltas.len(), WIDTH);
+        for (i, column) in game.position().columns.iter().enumerate() {
+            assert_eq!(column.len(), before.columns[i].len() + 1);
+        }
+
+        let deltas = game.undo().unwrap();
+        assert_eq!(deltas.len(), WIDTH);
+        for (i, delta) in deltas.iter().enumerate() {
+            assert!(matches!(delta, Delta::PopCard { index } if *index == i));
+        }
+        assert_eq!(game.position(), before);
+        assert_eq!(game.cards_dealt(), 54);
+        assert!(game.undo().is_err());
+    }
+
+    #[test]
+    fn undo_modes() {
+        use undo::UndoMode;
+
+        let game = || Game::from_seed("v1-0123456789abcdef0123456789abcdef".parse().unwrap());
+        let mut limited = game().unwrap().with_undo_mode(UndoMode::Limit(1));
+        limited.deal().unwrap();
+        limited.deal().unwrap();
+        assert!(limited.undo().is_ok());
+        assert!(matches!(
+            limited.undo(),
+            Err(GameError::UndoLimitReached { limit: 1 })
+        ));
+        assert_eq!(
+            limited.checkpoints().len(),
+            2,
+            "the refused undo undid nothing"
+        );
+        assert_eq!(limited.undos(), 1);
+
+        let mut none = game().unwrap().with_undo_mode(UndoMode::None);
+        none.deal().unwrap();
+        assert!(matches!(none.undo(), Err(GameError::UndoNotAllowed {})));
+        assert_eq!(none.undo_mode(), UndoMode::None);
+
+        let mut unlimited = game().unwrap();
+        for _ in 0..3 {
+            unlimited.deal().unwrap();
+            unlimited.undo().unwrap();
+        }
+        assert_eq!(unlimited.undos(), 3);
+    }
+
+    #[test]
+    fn undo_deal_with_completion() {
+        let mut game = Game::from_position(completion_position()).unwrap();
+        let before = game.position();
+        game.deal().unwrap();
+        let after = game.position();
+        assert_eq!(after.completed, vec![cards::suit::Suit::Spades]);
+        assert_eq!(after.columns[0].len(), 1);
+        assert!(matches!(after.columns[0][0], ColumnCard::Visible { .. }));
+        match game.checkpoints().last() {
+            Some(Checkpoint::Deal { completions, .. }) => assert_eq!(completions.len(), 1),
+            other => panic!("unexpected checkpoint {:?}", other),
+        }
+
+        game.undo().unwrap();
+        assert_eq!(game.position(), before);
+
+        game.deal().unwrap();
+        assert_eq!(game.position(), after);
+    }
+
+    #[test]
+    fn undo_move_with_completion() {
+        let mut game = Game::from_position(completion_position()).unwrap();
+        let before = game.position();
+        game.move_cards(Move {
+            orig_col: 1,
+            count: 1,
+            dest_col: 0,
+        })
+        .unwrap();
+        let after = game.position();
+        assert_eq!(after.completed, vec![cards::suit::Suit::Spades]);
+        assert_eq!(after.columns[0].len(), 1);
+        assert!(after.columns[1].is_empty());
+
+        game.undo().unwrap();
+        assert_eq!(game.position(), before);
+
+        // column 1 is empty after the move, so the strict rule refuses the deal
+        game.move_cards(Move {
+            orig_col: 1,
+            count: 1,
+            dest_col: 0,
+        })
+        .unwrap();
+        assert!(matches!(game.deal(), Err(GameError::DealToEmptyColumn {})));
+        assert_eq!(game.position(), after);
+    }
+
+    #[test]
+    fn packed_round_trip() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let hash = |packed: &packed::Packed| {
+            let mut hasher = DefaultHasher::new();
+            packed.hash(&mut hasher);
+            hasher.finish()
+        };
+        let rules = rules::Spider;
+        let m = Move {
+            orig_col: 1,
+            count: 1,
+            dest_col: 0,
+        };
+        let start = packed::Packed::from_position(&completion_position()).unwrap();
+
+        // both the move and the deal complete a run, then put it back
+        let mut game = Game::from_position(completion_position()).unwrap();
+        let mut packed = start;
+        let undo = packed.make_move(&rules, &m);
+        game.move_cards(m).unwrap();
+        assert_eq!(packed, packed::Packed::from_game(&game).unwrap());
+        packed.unmake_move(&m, undo);
+        assert_eq!(packed, start);
+        assert_eq!(hash(&packed), hash(&start));
+
+        let mut game = Game::from_position(completion_position()).unwrap();
+        let undo = packed.deal(&rules).unwrap();
+        game.deal().unwrap();
+        assert_eq!(packed, packed::Packed::from_game(&game).unwrap());
+        packed.undeal(undo);
+        assert_eq!(packed, start);
+        assert_eq!(hash(&packed), hash(&start));
+    }
+
+    #[test]
+    fn events() {
+        use event::Event;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let events: Rc<RefCell<Vec<Event>>> = Rc::new(RefCell::new(Vec::new()));
+        let mut game = Game::from_position(completion_position()).unwrap();
+        let log = events.clone();
+        game.subscribe(Box::new(move |event: &Event| {
+            log.borrow_mut().push(event.clone())
+        }));
+
+        let m = Move {
+            orig_col: 1,
+            count: 1,
+            dest_col: 0,
+        };
+        game.move_cards(m).unwrap();
+        let flipped = match game.position().columns[0][0] {
+            ColumnCard::Visible { card } => card,
+            ColumnCard::Hidden { .. } => panic!("completing the run should flip the card"),
+        };
+        let checkpoint = game.checkpoints().last().cloned().unwrap();
+        game.undo().unwrap();
+
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                Event::CardsMoved { mv: m },
+                Event::RunCompleted {
+                    index: 0,
+                    suit: cards::suit::Suit::Spades
+                },
+                Event::CardFlipped {
+                    index: 0,
+                    card: flipped
+                },
+                Event::Undone { checkpoint },
+            ]
+        );
+
+        events.borrow_mut().clear();
+        let mut game =
+            Game::from_seed("v1-0123456789abcdef0123456789abcdef".parse().unwrap()).unwrap();
+        let log = events.clone();
+        game.subscribe(Box::new(move |event: &Event| {
+            log.borrow_mut().push(event.clone())
+        }));
+        let stock = game.position().stock;
+        game.deal().unwrap();
+        assert_eq!(
+            *events.borrow(),
+            vec![Event::Dealt {
+                cards: stock[..WIDTH].to_vec()
+            }]
+        );
+    }
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        Deal,
+        // index into the list of valid moves, modulo its length
+        Move(usize),
+        Undo,
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            1 => Just(Op::Deal),
+            6 => any::<usize>().prop_map(Op::Move),
+            2 => Just(Op::Undo),
+        ]
+    }
+
+    fn empty_columns() -> impl Strategy<Value = rules::EmptyColumns> {
+        prop_oneof![
+            Just(rules::EmptyColumns::Strict),
+            Just(rules::EmptyColumns::Fill),
+            Just(rules::EmptyColumns::Skip),
+        ]
+    }
+
+    fn valid_moves(game: &Game) -> Vec<Move> {
+        let mut moves = Vec::<Move>::new();
+        for orig_col in 0..game.width() {
+            for dest_col in 0..game.width() {
+                for count in 1..=game.columns[orig_col].len() {
+                    let m = Move {
+                        orig_col,
+                        count,
+                        dest_col,
+                    };
+                    if game.is_move_valid(&m) {
+                        moves.push(m);
+                    }
+                }
+            }
+        }
+        moves
+    }
+
+    // apply performs the operation and returns true if the game changed
+    // in a way that undo should reverse
+    fn apply(game: &mut Game, op: &Op) -> bool {
+        match op {
+            Op::Deal => game.deal().is_ok(),
+            Op::Move(n) => {
+                let moves = valid_moves(game);
+                if moves.is_empty() {
+                    false
+                } else {
+                    game.move_cards(moves[n % moves.len()]).unwrap();
+                    true
+                }
+            }
+            Op::Undo => {
+                let _ = game.undo();
+                false
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn game_invariants(
+            variant in 0..layout::Layout::builtin().len(),
+            empty_columns in empty_columns(),
+            seed in any::<[u8; 16]>(),
+            ops in prop::collection::vec(op(), 0..150),
+        ) {
+            let layout = layout::Layout {
+                empty_columns,
+                ..layout::Layout::builtin().swap_remove(variant)
+            };
+            let mut game = Game::from_layout_seed(layout, seed::Seed::new(seed::Version::V1, seed)).unwrap();
+            for op in ops {
+                let before = game.position();
+                let checkpoint_count = game.checkpoints().len();
+                if apply(&mut game, &op) {
+                    // validate checks all cards are present and that no
+                    // hidden card lies above a visible one
+                    prop_assert!(game.position().validate(game.layout()).is_ok(), "{:?}", op);
+                    prop_assert_eq!(game.checkpoints().len(), checkpoint_count + 1);
+
+                    let after = game.position();
+                    game.undo().unwrap();
+                    prop_assert_eq!(&game.position(), &before, "undo {:?}", op);
+                    prop_assert_eq!(game.checkpoints().len(), checkpoint_count);
+
+                    prop_assert!(apply(&mut game, &op));
+                    prop_assert_eq!(game.position(), after, "redo {:?}", op);
+                } else {
+                    prop_assert!(game.position().validate(game.layout()).is_ok(), "{:?}", op);
+                }
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn packed_tracks_game(
+            variant in 0..layout::Layout::builtin().len(),
+            empty_columns in empty_columns(),
+            seed in any::<[u8; 16]>(),
+            ops in prop::collection::vec(op(), 0..150),
+        ) {
+            let layout = layout::Layout {
+                empty_columns,
+                ..layout::Layout::builtin().swap_remove(variant)
+            };
+            let mut game = Game::from_layout_seed(layout, seed::Seed::new(seed::Version::V1, seed)).unwrap();
+            let mut packed = packed::Packed::from_game(&game).unwrap();
+            let rules = rules::for_layout(game.layout());
+            let mut undos: Vec<(Option<Move>, packed::Undo)> = Vec::new();
+            for op in ops {
+                match op {
+                    Op::Deal => {
+                        let undo = packed.deal(rules.as_ref());
+                        prop_assert_eq!(undo.is_some(), game.deal().is_ok());
+                        if let Some(undo) = undo {
+                            undos.push((None, undo));
+                        }
+                    }
+                    Op::Move(n) => {
+                        let moves = valid_moves(&game);
+                        if !moves.is_empty() {
+                            let m = moves[n % moves.len()];
+                            prop_assert!(packed.is_move_valid(rules.as_ref(), &m));
+                            game.move_cards(m).unwrap();
+                            undos.push((Some(m), packed.make_move(rules.as_ref(), &m)));
+                        }
+                    }
+                    Op::Undo => {
+                        if let Some((m, undo)) = undos.pop() {
+                            game.undo().unwrap();
+                            match m {
+                                Some(m) => packed.unmake_move(&m, undo),
+                                None => packed.undeal(undo),
+                            }
+                        }
+                    }
+                }
+                prop_assert_eq!(packed.to_position(), game.position(), "{:?}", op);
+                prop_assert_eq!(packed, packed::Packed::from_game(&game).unwrap(), "{:?}", op);
+                let top_cards = packed.top_cards();
+                for column in game.position().columns {
+                    if let Some(ColumnCard::Visible { card }) = column.last() {
+                        prop_assert!(top_cards.contains(*card));
+                    }
+                }
+            }
+            prop_assert_eq!(packed.to_game(game.layout().clone()).unwrap().position(), game.position());
+        }
+    }
 }
@@ -0,0 +1,382 @@
+// packed is a compact position for search. It holds the whole layout in
+// fixed size arrays of card index bytes, so cloning, comparing and hashing
+// never allocate, and moves and deals are made and unmade in place.
+//
+// Judging moves, deals and completed runs is left to the rules, passed in
+// by the caller, for any layout that fits.
+
+use super::error::GameError;
+use super::layout::Layout;
+use super::position::Position;
+use super::rules::Rules;
+use super::{ColumnCard, Game, Move};
+
+pub const MAX_COLUMNS: usize = 10;
+pub const MAX_CARDS: usize = 104;
+const RUN_LENGTH: usize = 13;
+const MAX_RUNS: usize = MAX_CARDS / RUN_LENGTH;
+
+/// CardSet is a set of distinct cards as a bitset over Card::index
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CardSet(u64);
+
+impl CardSet {
+    pub fn new() -> CardSet {
+        CardSet(0)
+    }
+
+    pub fn insert(&mut self, card: cards::Card) {
+        self.0 |= 1 << card.index();
+    }
+
+    pub fn remove(&mut self, card: cards::Card) {
+        self.0 &= !(1 << card.index());
+    }
+
+    pub fn contains(&self, card: cards::Card) -> bool {
+        self.0 & (1 << card.index()) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn union(&self, other: CardSet) -> CardSet {
+        CardSet(self.0 | other.0)
+    }
+
+    pub fn intersection(&self, other: CardSet) -> CardSet {
+        CardSet(self.0 & other.0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = cards::Card> + '_ {
+        cards::Card::iter().filter(move |c| self.contains(*c))
+    }
+}
+
+impl std::iter::FromIterator<cards::Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = cards::Card>>(iter: I) -> CardSet {
+        let mut set = CardSet::new();
+        for card in iter {
+            set.insert(card);
+        }
+        set
+    }
+}
+
+// Undo records what a move or a deal did beyond moving cards,
+// so that it can be unmade
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Undo {
+    flipped_hidden_card: bool,
+    // bit i is set when a deal dealt a card to column i
+    dealt: u16,
+    // bit i is set when a run was completed in column i
+    completed: u16,
+    // bit i is set when completing the run in column i flipped a card
+    completion_flipped: u16,
+}
+
+// the bytes beyond the columns, the stock and the completed runs are kept
+// zero, so that equal positions compare and hash equal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Packed {
+    width: u8,
+    // column i is cards[start(i)..start(i) + lens[i]], bottom card first,
+    // and its first hidden[i] cards are face down
+    lens: [u8; MAX_COLUMNS],
+    hidden: [u8; MAX_COLUMNS],
+    // the columns back to back from the front,
+    // the stock at the back with its top card first
+    cards: [u8; MAX_CARDS],
+    stock: u8,
+    // suit indices of the completed runs, in order of completion
+    completed: [u8; MAX_RUNS],
+    runs: u8,
+}
+
+impl Packed {
+    pub fn from_position(position: &Position) -> Result<Packed, GameError> {
+        let too_big = |reason: &str| GameError::InvalidPosition {
+            reason: reason.to_string(),
+        };
+        if position.columns.len() > MAX_COLUMNS {
+            return Err(too_big("too many columns to pack"));
+        }
+        let count: usize = position.columns.iter().map(|c| c.len()).sum();
+        if count + position.stock.len() > MAX_CARDS || position.completed.len() > MAX_RUNS {
+            return Err(too_big("too many cards to pack"));
+        }
+
+        let mut packed = Packed {
+            width: position.columns.len() as u8,
+            lens: [0; MAX_COLUMNS],
+            hidden: [0; MAX_COLUMNS],
+            cards: [0; MAX_CARDS],
+            stock: position.stock.len() as u8,
+            completed: [0; MAX_RUNS],
+            runs: position.completed.len() as u8,
+        };
+        let mut at = 0;
+        for (i, column) in position.columns.iter().enumerate() {
+            packed.lens[i] = column.len() as u8;
+            for column_card in column {
+                match column_card {
+                    ColumnCard::Hidden { card } => {
+                        packed.hidden[i] += 1;
+                        packed.cards[at] = card.index();
+                    }
+                    ColumnCard::Visible { card } => packed.cards[at] = card.index(),
+                }
+                at += 1;
+            }
+        }
+        let stock_start = MAX_CARDS - position.stock.len();
+        for (i, card) in position.stock.iter().enumerate() {
+            packed.cards[stock_start + i] = card.index();
+        }
+        for (i, suit) in position.completed.iter().enumerate() {
+            packed.completed[i] = suit.index();
+        }
+
+        Ok(packed)
+    }
+
+    pub fn from_game(game: &Game) -> Result<Packed, GameError> {
+        Packed::from_position(&game.position())
+    }
+
+    // to_game starts a new game of the layout from this position,
+    // with nothing to undo
+    pub fn to_game(&self, layout: Layout) -> Result<Game, GameError> {
+        Game::from_layout_position(layout, self.to_position())
+    }
+
+    pub fn to_position(&self) -> Position {
+        let mut columns: Vec<Vec<ColumnCard>> = Vec::new();
+        for i in 0..self.width() {
+            let column = self
+                .column(i)
+                .iter()
+                .enumerate()
+                .map(|(j, byte)| {
+                    let card = card(*byte);
+                    if j < self.hidden[i] as usize {
+                        ColumnCard::Hidden { card }
+                    } else {
+                        ColumnCard::Visible { card }
+                    }
+                })
+                .collect();
+            columns.push(column);
+        }
+        Position {
+            columns,
+            stock: self.cards[MAX_CARDS - self.stock as usize..]
+                .iter()
+                .map(|byte| card(*byte))
+                .collect(),
+            completed: self.completed[..self.runs as usize]
+                .iter()
+                .map(|byte| cards::suit::Suit::from_index(*byte).unwrap())
+                .collect(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width as usize
+    }
+
+    // column is the card index bytes of column i, bottom card first
+    pub fn column(&self, i: usize) -> &[u8] {
+        let start = self.start(i);
+        &self.cards[start..start + self.lens[i] as usize]
+    }
+
+    pub fn hidden(&self, i: usize) -> usize {
+        self.hidden[i] as usize
+    }
+
+    pub fn stock_len(&self) -> usize {
+        self.stock as usize
+    }
+
+    pub fn completed_runs(&self) -> usize {
+        self.runs as usize
+    }
+
+    // top_cards is the set of visible cards at the top of the columns
+    pub fn top_cards(&self) -> CardSet {
+        (0..self.width())
+            .filter(|i| self.lens[*i] > self.hidden[*i])
+            .map(|i| card(*self.column(i).last().unwrap()))
+            .collect()
+    }
+
+    pub fn is_move_valid(&self, rules: &dyn Rules, m: &Move) -> bool {
+        let width = self.width();
+        if m.orig_col >= width || m.dest_col >= width || m.orig_col == m.dest_col || m.count == 0 {
+            return false;
+        }
+        let visible = (self.lens[m.orig_col] - self.hidden[m.orig_col]) as usize;
+        if m.count > visible {
+            return false;
+        }
+        let orig = self.column(m.orig_col);
+        let mut moving = [card(0); MAX_CARDS];
+        let moving = cards_of(&orig[orig.len() - m.count..], &mut moving);
+        let dest = self.column(m.dest_col).last().map(|byte| card(*byte));
+        rules.is_move_valid(moving, dest)
+    }
+
+    // make_move moves the cards, flips the origin column and completes
+    // a run in the destination column. The move must be valid.
+    pub fn make_move(&mut self, rules: &dyn Rules, m: &Move) -> Undo {
+        let mut undo = Undo::default();
+        let mut moving = [0; MAX_CARDS];
+        let moving = self.take(m.orig_col, m.count, &mut moving);
+        self.append(m.dest_col, moving);
+        undo.flipped_hidden_card = self.flip(m.orig_col);
+        self.complete_run(rules, m.dest_col, &mut undo);
+        undo
+    }
+
+    pub fn unmake_move(&mut self, m: &Move, undo: Undo) {
+        self.restore_runs(undo);
+        let mut moving = [0; MAX_CARDS];
+        let moving = self.take(m.dest_col, m.count, &mut moving);
+        self.append(m.orig_col, moving);
+        if undo.flipped_hidden_card {
+            self.hidden[m.orig_col] += 1;
+        }
+    }
+
+    // deal deals a card onto the columns the rules choose, or returns None
+    // if the stock is empty or the rules say it can't be dealt
+    pub fn deal(&mut self, rules: &dyn Rules) -> Option<Undo> {
+        let width = self.width();
+        if self.stock == 0 {
+            return None;
+        }
+        let dealt = rules
+            .deal_columns(&self.lens[..width], self.stock as usize)
+            .ok()?;
+        let mut undo = Undo {
+            dealt,
+            ..Undo::default()
+        };
+        for i in (0..width).filter(|i| dealt & (1 << i) != 0) {
+            let top = self.cards[MAX_CARDS - self.stock as usize];
+            self.cards[MAX_CARDS - self.stock as usize] = 0;
+            self.stock -= 1;
+            self.append(i, &[top]);
+        }
+        for i in 0..width {
+            self.complete_run(rules, i, &mut undo);
+        }
+        Some(undo)
+    }
+
+    pub fn undeal(&mut self, undo: Undo) {
+        self.restore_runs(undo);
+        for i in (0..self.width()).rev() {
+            if undo.dealt & (1 << i) == 0 {
+                continue;
+            }
+            let mut top = [0; 1];
+            let top = self.take(i, 1, &mut top)[0];
+            self.stock += 1;
+            self.cards[MAX_CARDS - self.stock as usize] = top;
+        }
+    }
+
+    fn start(&self, i: usize) -> usize {
+        self.lens[..i].iter().map(|len| *len as usize).sum()
+    }
+
+    // take removes the top count cards of column i into buffer
+    fn take<'a>(&mut self, i: usize, count: usize, buffer: &'a mut [u8]) -> &'a [u8] {
+        let end = self.start(self.width());
+        let from = self.start(i) + self.lens[i] as usize - count;
+        buffer[..count].copy_from_slice(&self.cards[from..from + count]);
+        self.cards.copy_within(from + count..end, from);
+        self.cards[end - count..end].fill(0);
+        self.lens[i] -= count as u8;
+        &buffer[..count]
+    }
+
+    fn append(&mut self, i: usize, bytes: &[u8]) {
+        let end = self.start(self.width());
+        let at = self.start(i) + self.lens[i] as usize;
+        self.cards.copy_within(at..end, at + bytes.len());
+        self.cards[at..at + bytes.len()].copy_from_slice(bytes);
+        self.lens[i] += bytes.len() as u8;
+    }
+
+    // flip turns the top card of column i face up if it is hidden
+    fn flip(&mut self, i: usize) -> bool {
+        if self.lens[i] > 0 && self.hidden[i] == self.lens[i] {
+            self.hidden[i] -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn complete_run(&mut self, rules: &dyn Rules, i: usize, undo: &mut Undo) {
+        let visible = (self.lens[i] - self.hidden[i]) as usize;
+        if visible < RUN_LENGTH {
+            return;
+        }
+        let column = self.column(i);
+        let suit = column[column.len() - RUN_LENGTH] / RUN_LENGTH as u8;
+        let mut run = [card(0); RUN_LENGTH];
+        if !rules.is_complete_run(cards_of(&column[column.len() - RUN_LENGTH..], &mut run)) {
+            return;
+        }
+        let mut removed = [0; RUN_LENGTH];
+        self.take(i, RUN_LENGTH, &mut removed);
+        self.completed[self.runs as usize] = suit;
+        self.runs += 1;
+        undo.completed |= 1 << i;
+        if self.flip(i) {
+            undo.completion_flipped |= 1 << i;
+        }
+    }
+
+    // restore_runs puts completed runs back, last completed first
+    fn restore_runs(&mut self, undo: Undo) {
+        for i in (0..self.width()).rev() {
+            if undo.completed & (1 << i) == 0 {
+                continue;
+            }
+            if undo.completion_flipped & (1 << i) != 0 {
+                self.hidden[i] += 1;
+            }
+            self.runs -= 1;
+            let suit = self.completed[self.runs as usize];
+            self.completed[self.runs as usize] = 0;
+            let mut run = [0; RUN_LENGTH];
+            for (j, byte) in run.iter_mut().enumerate() {
+                *byte = suit * RUN_LENGTH as u8 + (RUN_LENGTH - 1 - j) as u8;
+            }
+            self.append(i, &run);
+        }
+    }
+}
+
+fn card(byte: u8) -> cards::Card {
+    cards::Card::from_index(byte).unwrap()
+}
+
+// cards_of converts index bytes into buffer
+fn cards_of<'a>(bytes: &[u8], buffer: &'a mut [cards::Card]) -> &'a [cards::Card] {
+    for (c, byte) in buffer.iter_mut().zip(bytes) {
+        *c = card(*byte);
+    }
+    &buffer[..bytes.len()]
+}
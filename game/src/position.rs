@@ -0,0 +1,250 @@
+// position describes a game in progress as text, so puzzles and
+// regression tests can start from any layout.
+//
+//     # comment
+//     stock: 5H 6C ...
+//     completed: S H
+//     column: [QH] [5C] 4D 3D
+//     column:
+//
+// There is one "column:" line per column, listed bottom card first;
+// hidden cards are written in brackets. The stock is listed in deal order.
+// A completed run is written as the suit of its King to Ace sequence.
+// Parsing checks only the text; validate checks the position against
+// the layout of a variant.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use super::deck;
+use super::error::GameError;
+use super::layout::Layout;
+use super::rules::EmptyColumns;
+use super::ColumnCard;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub columns: Vec<Vec<ColumnCard>>,
+    pub stock: Vec<cards::Card>,
+    pub completed: Vec<cards::suit::Suit>,
+}
+
+impl Position {
+    // validate checks that the position could occur in a game of the
+    // layout: all the cards are accounted for, every column has its hidden
+    // cards below its visible ones and the stock holds whole deals
+    pub fn validate(&self, layout: &Layout) -> Result<(), GameError> {
+        let width = layout.width();
+        if self.columns.len() != width {
+            return Err(invalid(format!(
+                "{} columns, expected {}",
+                self.columns.len(),
+                width
+            )));
+        }
+        for (i, column) in self.columns.iter().enumerate() {
+            let mut seen_visible = false;
+            for column_card in column {
+                match column_card {
+                    ColumnCard::Visible { .. } => seen_visible = true,
+                    ColumnCard::Hidden { .. } if seen_visible => {
+                        return Err(invalid(format!(
+                            "column {} has a hidden card above a visible one",
+                            i
+                        )));
+                    }
+                    ColumnCard::Hidden { .. } => {}
+                }
+            }
+            if !column.is_empty() && !seen_visible {
+                return Err(invalid(format!("column {} has no visible card", i)));
+            }
+        }
+        // only the last deal of a variant may be short, unless deals
+        // skip empty columns
+        let last_deal = (layout.total_cards() - layout.initial_cards()) % width;
+        if layout.empty_columns != EmptyColumns::Skip
+            && !self.stock.is_empty()
+            && self.stock.len() % width != last_deal
+        {
+            return Err(invalid(format!(
+                "stock of {} cards is not a whole number of deals",
+                self.stock.len()
+            )));
+        }
+
+        layout.validate_deck(&self.cards())
+    }
+
+    // cards lists every card in the position, including completed runs
+    pub fn cards(&self) -> Vec<cards::Card> {
+        let mut all: Vec<cards::Card> = Vec::new();
+        for column in &self.columns {
+            for column_card in column {
+                match column_card {
+                    ColumnCard::Hidden { card } | ColumnCard::Visible { card } => all.push(*card),
+                }
+            }
+        }
+        all.extend(self.stock.iter());
+        for suit in &self.completed {
+            for rank in cards::rank::iter() {
+                all.push(cards::Card { suit: *suit, rank });
+            }
+        }
+        all
+    }
+}
+
+fn invalid(reason: String) -> GameError {
+    GameError::InvalidPosition { reason }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let stock: Vec<String> = self.stock.iter().map(|c| deck::format_card(*c)).collect();
+        writeln!(f, "stock: {}", stock.join(" "))?;
+        let completed: Vec<String> = self.completed.iter().map(|s| s.to_string()).collect();
+        writeln!(f, "completed: {}", completed.join(" "))?;
+        for column in &self.columns {
+            let cards: Vec<String> = column
+                .iter()
+                .map(|column_card| match column_card {
+                    ColumnCard::Hidden { card } => format!("[{}]", deck::format_card(*card)),
+                    ColumnCard::Visible { card } => deck::format_card(*card),
+                })
+                .collect();
+            writeln!(f, "column: {}", cards.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Position {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Position, GameError> {
+        let mut position = Position {
+            columns: Vec::new(),
+            stock: Vec::new(),
+            completed: Vec::new(),
+        };
+        for line in s.lines() {
+            let line = match line.find('#') {
+                Some(i) => &line[..i],
+                None => line,
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| invalid(format!("expected 'key: value', found '{}'", line)))?;
+            let tokens = value.split_whitespace();
+            match key.trim() {
+                "stock" => {
+                    for token in tokens {
+                        position.stock.push(deck::parse_card(token)?);
+                    }
+                }
+                "completed" => {
+                    for token in tokens {
+                        position.completed.push(token.parse::<cards::suit::Suit>()?);
+                    }
+                }
+                "column" => {
+                    let mut column: Vec<ColumnCard> = Vec::new();
+                    for token in tokens {
+                        let column_card = match token.strip_prefix('[') {
+                            Some(rest) => ColumnCard::Hidden {
+                                card: deck::parse_card(rest.strip_suffix(']').ok_or_else(
+                                    || invalid(format!("unclosed hidden card '{}'", token)),
+                                )?)?,
+                            },
+                            None => ColumnCard::Visible {
+                                card: deck::parse_card(token)?,
+                            },
+                        };
+                        column.push(column_card);
+                    }
+                    position.columns.push(column);
+                }
+                other => return Err(invalid(format!("unknown key '{}'", other))),
+            }
+        }
+        Ok(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Game;
+
+    fn game() -> Game {
+        Game::from_seed("v1-0123456789abcdef0123456789abcdef".parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let position = game().position();
+        let text = position.to_string();
+        assert_eq!(text.parse::<Position>().unwrap(), position);
+
+        let replayed = Game::from_position(position.clone()).unwrap();
+        assert_eq!(replayed.position(), position);
+        assert_eq!(replayed.total_cards(), 104);
+        assert_eq!(replayed.cards_dealt(), 54);
+    }
+
+    #[test]
+    fn invalid_positions() {
+        let spider = Layout::spider();
+        let mut position = game().position();
+        position.columns[0].swap(0, 5);
+        assert!(position.validate(&spider).is_err(), "hidden above visible");
+        position.columns[0].swap(0, 5);
+        assert!(position.validate(&spider).is_ok());
+
+        let card = position.stock.pop().unwrap();
+        assert!(position.validate(&spider).is_err(), "partial deal");
+        position.stock.insert(0, card);
+        position.completed.push(cards::suit::Suit::Hearts);
+        assert!(position.validate(&spider).is_err(), "extra cards");
+        position.completed.pop();
+        assert!(
+            position.validate(&Layout::spider8()).is_err(),
+            "wrong width"
+        );
+    }
+
+    #[test]
+    fn short_last_deal() {
+        // spiderette deals 24 cards in three deals of seven and one of three
+        let spiderette = Layout::spiderette();
+        let mut game = Game::from_layout_seed(
+            spiderette.clone(),
+            "v1-0123456789abcdef0123456789abcdef".parse().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(game.position().stock.len(), 24);
+        for _ in 0..3 {
+            game.deal().unwrap();
+            assert!(game.position().validate(&spiderette).is_ok());
+        }
+        let before = game.position();
+        game.deal().unwrap();
+        let after = game.position();
+        assert!(after.stock.is_empty());
+        assert!(after.validate(&spiderette).is_ok());
+        let grew: Vec<usize> = (0..7)
+            .map(|i| after.columns[i].len() - before.columns[i].len())
+            .collect();
+        assert_eq!(grew, vec![1, 1, 1, 0, 0, 0, 0]);
+        assert!(matches!(game.deal(), Err(GameError::DealFromEmptyDeck {})));
+
+        game.undo().unwrap();
+        assert_eq!(game.position(), before);
+    }
+}
@@ -0,0 +1,303 @@
+// rules judge the moves and deals of a variant. The engine keeps the
+// columns, the stock and the checkpoints the same way for every variant
+// and consults the rules for what is allowed, so a variant or a house rule
+// is a type implementing Rules, overriding only what differs from Spider.
+//
+// Spider moves a same suit descending run onto a card one rank higher of
+// any suit, or onto an empty column, and deals only when no column is empty.
+// Scorpion moves any face up card with everything on top of it, builds in
+// suit only, allows only Kings into empty columns and deals regardless.
+//
+// A layout may relax the deal with the house rule for empty columns:
+// strict keeps the rules, fill deals onto empty columns too and skip deals
+// only onto the columns that have cards.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use cards::rank::Rank;
+
+use super::error::GameError;
+use super::layout::Layout;
+
+// Rules defaults to the Spider rules
+pub trait Rules: fmt::Debug {
+    // is_move_valid judges moving the cards, bottom card first, onto dest,
+    // the top card of the destination column or None if it is empty.
+    // The engine has checked that the cards are face up.
+    fn is_move_valid(&self, moving: &[cards::Card], dest: Option<cards::Card>) -> bool {
+        match moving.first() {
+            None => false,
+            Some(bottom) => {
+                cards::is_descending_run(moving)
+                    && dest.is_none_or(|d| cards::rank::successor(bottom.rank) == Some(d.rank))
+            }
+        }
+    }
+
+    // deal_columns is the columns a deal puts a card on, with bit i set for
+    // column i, given the number of cards in each column and in the stock,
+    // which is never empty; or says why the stock can't be dealt
+    fn deal_columns(&self, columns: &[u8], stock: usize) -> Result<u16, GameError> {
+        if columns.contains(&0) {
+            return Err(GameError::DealToEmptyColumn {});
+        }
+        Ok(first_columns(columns.len().min(stock)))
+    }
+
+    // is_complete_run judges the top RUN_LENGTH cards of a column, all face
+    // up, bottom card first; a complete run leaves play. Undo puts back the
+    // King to Ace of the suit of the first card, so no other run can be
+    // complete.
+    fn is_complete_run(&self, run: &[cards::Card]) -> bool {
+        run.first().is_some_and(|c| c.rank == Rank::King) && cards::is_descending_run(run)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Spider;
+
+impl Rules for Spider {}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Scorpion;
+
+impl Rules for Scorpion {
+    fn is_move_valid(&self, moving: &[cards::Card], dest: Option<cards::Card>) -> bool {
+        match (moving.first(), dest) {
+            (None, _) => false,
+            (Some(bottom), None) => bottom.rank == Rank::King,
+            (Some(bottom), Some(d)) => {
+                d.suit == bottom.suit && cards::rank::successor(bottom.rank) == Some(d.rank)
+            }
+        }
+    }
+
+    fn deal_columns(&self, columns: &[u8], stock: usize) -> Result<u16, GameError> {
+        Ok(first_columns(columns.len().min(stock)))
+    }
+}
+
+// first_columns is the bitmask of the first count columns
+pub fn first_columns(count: usize) -> u16 {
+    ((1u32 << count) - 1) as u16
+}
+
+// RuleSet names the built in rules, so a layout from configuration
+// can choose them
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleSet {
+    #[default]
+    Spider,
+    Scorpion,
+}
+
+impl RuleSet {
+    pub fn rules(&self) -> Box<dyn Rules> {
+        match self {
+            RuleSet::Spider => Box::new(Spider),
+            RuleSet::Scorpion => Box::new(Scorpion),
+        }
+    }
+}
+
+// for_layout is the rules a layout is played by, house rule included
+pub fn for_layout(layout: &Layout) -> Box<dyn Rules> {
+    let rules = layout.rules.rules();
+    match layout.empty_columns {
+        EmptyColumns::Strict => rules,
+        empty_columns => Box::new(HouseRules {
+            rules,
+            empty_columns,
+        }),
+    }
+}
+
+// EmptyColumns is the house rule for dealing while a column is empty
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmptyColumns {
+    #[default]
+    Strict,
+    Fill,
+    Skip,
+}
+
+impl EmptyColumns {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EmptyColumns::Strict => "strict",
+            EmptyColumns::Fill => "fill",
+            EmptyColumns::Skip => "skip",
+        }
+    }
+}
+
+impl fmt::Display for EmptyColumns {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for EmptyColumns {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<EmptyColumns, GameError> {
+        match s {
+            "strict" => Ok(EmptyColumns::Strict),
+            "fill" => Ok(EmptyColumns::Fill),
+            "skip" => Ok(EmptyColumns::Skip),
+            _ => Err(GameError::UnknownEmptyColumns {
+                name: s.to_string(),
+            }),
+        }
+    }
+}
+
+// HouseRules plays by the rules, but deals by the house rule
+#[derive(Debug)]
+pub struct HouseRules {
+    pub rules: Box<dyn Rules>,
+    pub empty_columns: EmptyColumns,
+}
+
+impl Rules for HouseRules {
+    fn is_move_valid(&self, moving: &[cards::Card], dest: Option<cards::Card>) -> bool {
+        self.rules.is_move_valid(moving, dest)
+    }
+
+    fn deal_columns(&self, columns: &[u8], stock: usize) -> Result<u16, GameError> {
+        let width = columns.len();
+        match self.empty_columns {
+            EmptyColumns::Strict => self.rules.deal_columns(columns, stock),
+            EmptyColumns::Fill => Ok(first_columns(width.min(stock))),
+            // with every column empty there is nothing to skip to
+            EmptyColumns::Skip if columns.iter().all(|len| *len == 0) => {
+                Ok(first_columns(width.min(stock)))
+            }
+            EmptyColumns::Skip => Ok((0..width)
+                .filter(|i| columns[*i] > 0)
+                .take(stock)
+                .fold(0, |mask, i| mask | 1 << i)),
+        }
+    }
+
+    fn is_complete_run(&self, run: &[cards::Card]) -> bool {
+        self.rules.is_complete_run(run)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // columns lists the columns of a deal_columns bitmask
+    fn columns(mask: u16) -> Vec<usize> {
+        (0..16).filter(|i| mask & (1 << i) != 0).collect()
+    }
+
+    fn parse(text: &str) -> Vec<cards::Card> {
+        text.split_whitespace()
+            .map(|token| crate::deck::parse_card(token).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn spider() {
+        let rules = RuleSet::Spider.rules();
+        let dest = Some(parse("6D")[0]);
+        assert!(rules.is_move_valid(&parse("5S 4S"), dest));
+        assert!(rules.is_move_valid(&parse("5S 4S"), None));
+        assert!(!rules.is_move_valid(&parse("5S 4H"), dest), "not a run");
+        assert!(!rules.is_move_valid(&parse("4S"), dest));
+        assert!(!rules.is_move_valid(&[], dest));
+        assert_eq!(
+            columns(rules.deal_columns(&[1, 2, 3], 2).unwrap()),
+            vec![0, 1]
+        );
+        assert_eq!(
+            columns(rules.deal_columns(&[1; 10], 50).unwrap()),
+            (0..10).collect::<Vec<usize>>()
+        );
+        assert!(matches!(
+            rules.deal_columns(&[1, 0, 3], 10),
+            Err(GameError::DealToEmptyColumn {})
+        ));
+        let run = parse("KC QC JC XC 9C 8C 7C 6C 5C 4C 3C 2C AC");
+        assert!(rules.is_complete_run(&run));
+        assert!(!rules.is_complete_run(&parse("KC QC JC XC 9C 8C 7C 6C 5C 4C 3C 2C AD")));
+    }
+
+    #[test]
+    fn scorpion() {
+        let rules = RuleSet::Scorpion.rules();
+        assert!(rules.is_move_valid(&parse("5S 9H 2C"), Some(parse("6S")[0])));
+        assert!(
+            !rules.is_move_valid(&parse("5S"), Some(parse("6D")[0])),
+            "off suit"
+        );
+        assert!(rules.is_move_valid(&parse("KH 3C"), None));
+        assert!(
+            !rules.is_move_valid(&parse("QH"), None),
+            "only Kings to empty columns"
+        );
+        assert_eq!(
+            columns(rules.deal_columns(&[0, 2, 3, 4, 0, 6, 7], 3).unwrap()),
+            vec![0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn empty_columns() {
+        let mut layout = Layout::spider8();
+        let lens = [3, 0, 4, 0, 1, 1, 1, 1];
+        assert!(for_layout(&layout).deal_columns(&lens, 16).is_err());
+        layout.empty_columns = EmptyColumns::Fill;
+        assert_eq!(
+            columns(for_layout(&layout).deal_columns(&lens, 16).unwrap()),
+            (0..8).collect::<Vec<usize>>()
+        );
+        layout.empty_columns = EmptyColumns::Skip;
+        let rules = for_layout(&layout);
+        assert_eq!(
+            columns(rules.deal_columns(&lens, 16).unwrap()),
+            vec![0, 2, 4, 5, 6, 7]
+        );
+        assert_eq!(columns(rules.deal_columns(&lens, 2).unwrap()), vec![0, 2]);
+        assert_eq!(
+            columns(rules.deal_columns(&[0; 8], 3).unwrap()),
+            vec![0, 1, 2]
+        );
+
+        assert_eq!("fill".parse::<EmptyColumns>().unwrap(), EmptyColumns::Fill);
+        assert!("lenient".parse::<EmptyColumns>().is_err());
+    }
+
+    // a house rule overrides only what it changes
+    #[derive(Debug)]
+    struct AnyCardToEmpty;
+
+    impl Rules for AnyCardToEmpty {
+        fn is_move_valid(&self, moving: &[cards::Card], dest: Option<cards::Card>) -> bool {
+            dest.is_none() || Spider.is_move_valid(moving, dest)
+        }
+    }
+
+    #[test]
+    fn house_rules() {
+        let mut game = crate::Game::from_rules_seed(
+            Layout::spiderette(),
+            Box::new(AnyCardToEmpty),
+            crate::seed::from_random(),
+        )
+        .unwrap();
+        assert_eq!(game.layout(), &Layout::spiderette());
+        assert!(game.rules().is_move_valid(&parse("5S 4H"), None));
+        assert!(!game
+            .rules()
+            .is_move_valid(&parse("5S 4H"), Some(parse("6S")[0])));
+        assert!(game.deal().is_ok());
+    }
+}
@@ -1,4 +1,61 @@
 use rand::random;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+
+use super::error::GameError;
+
+/// Version selects the shuffle algorithm a seed was recorded with.
+/// A seed only reproduces a deal when paired with the same version.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Version {
+    /// rand 0.5 XorShiftRng and Rng::shuffle; written as bare hex
+    Legacy,
+    /// the game crate's own shuffle; written as "v1-<hex>"
+    #[default]
+    V1,
+}
+
+/// the version used for newly generated seeds
+pub const CURRENT: Version = Version::V1;
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Seed {
+    pub version: Version,
+    pub value: [u8; 16],
+}
+
+impl Seed {
+    pub fn new(version: Version, value: [u8; 16]) -> Seed {
+        Seed { version, value }
+    }
+}
+
+impl fmt::Display for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.version {
+            Version::Legacy => write!(f, "{}", hex::encode(self.value)),
+            Version::V1 => write!(f, "v1-{}", hex::encode(self.value)),
+        }
+    }
+}
+
+impl FromStr for Seed {
+    type Err = GameError;
+
+    // a bare hex string is a legacy seed, so previously recorded seeds
+    // keep producing the same deal
+    fn from_str(s: &str) -> Result<Seed, GameError> {
+        let s = s.trim();
+        match s.split_once('-') {
+            None => Ok(Seed::new(Version::Legacy, from_hex(s)?)),
+            Some(("v1", hex_string)) => Ok(Seed::new(Version::V1, from_hex(hex_string)?)),
+            Some((version, _)) => Err(GameError::UnknownSeedVersion {
+                version: version.to_string(),
+            }),
+        }
+    }
+}
 
 pub fn from_hex(hex_string: &str) -> Result<[u8; 16], hex::FromHexError> {
     let seed_vec = hex::decode(hex_string)?;
@@ -12,11 +69,37 @@ pub fn from_hex(hex_string: &str) -> Result<[u8; 16], hex::FromHexError> {
     Ok(seed)
 }
 
-pub fn from_random() -> [u8; 16] {
+pub fn from_random() -> Seed {
     let mut seed: [u8; 16] = [0; 16];
     for item in &mut seed {
         *item = random::<u8>();
     }
 
-    seed
+    Seed::new(CURRENT, seed)
+}
+
+/// daily is the seed of the daily deal for a date, written as "2024-05-01",
+/// the same for everyone on the day. It is pinned to V1 rather than CURRENT
+/// so that a day's deal never changes when a new version comes along.
+pub fn daily(date: &str) -> Seed {
+    let digest = Sha256::digest(format!("spider daily {}", date).as_bytes());
+    let mut value: [u8; 16] = [0; 16];
+    value.copy_from_slice(&digest[..16]);
+    Seed::new(Version::V1, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daily_is_stable() {
+        assert_eq!(daily("2024-05-01"), daily("2024-05-01"));
+        assert_ne!(daily("2024-05-01"), daily("2024-05-02"));
+        // recorded daily deals must keep their seeds
+        assert_eq!(
+            daily("2024-05-01").to_string(),
+            "v1-e95c6553aa86810e5ee6cbfe556e57e7"
+        );
+    }
 }
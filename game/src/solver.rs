@@ -0,0 +1,266 @@
+// solver searches for a winning line of play from a position, depth first
+// over packed positions, never visiting a position twice.
+//
+// The solver sees the hidden cards and the order of the stock, so a
+// position it cannot solve cannot be won by any player; one it solves
+// may still be lost by a player who cannot see the cards.
+
+use std::collections::HashSet;
+
+use super::packed::{Packed, Undo};
+use super::rules::Rules;
+use super::Move;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Step {
+    Move(Move),
+    Deal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    // the steps that win the game, in order
+    Solved(Vec<Step>),
+    // every reachable position was searched
+    Unsolvable,
+    // the search stopped at the node limit
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub verdict: Verdict,
+    // the number of distinct positions visited
+    pub nodes: usize,
+}
+
+struct Frame {
+    steps: Vec<Step>,
+    next: usize,
+    // the step that led to this frame's position, to unmake on the way back
+    made: Option<(Step, Undo)>,
+}
+
+// solve visits at most limit positions, playing by the rules
+pub fn solve(start: &Packed, rules: &dyn Rules, limit: usize) -> Report {
+    let mut position = *start;
+    if is_won(&position) {
+        return Report {
+            verdict: Verdict::Solved(Vec::new()),
+            nodes: 0,
+        };
+    }
+
+    let mut seen: HashSet<Packed> = HashSet::new();
+    seen.insert(position);
+    let mut nodes = 0;
+    let mut stack = vec![Frame {
+        steps: steps(&position, rules),
+        next: 0,
+        made: None,
+    }];
+
+    while let Some(frame) = stack.last_mut() {
+        if frame.next == frame.steps.len() {
+            if let Some((step, undo)) = frame.made {
+                unmake(&mut position, step, undo);
+            }
+            stack.pop();
+            continue;
+        }
+        let step = frame.steps[frame.next];
+        frame.next += 1;
+
+        let undo = make(&mut position, rules, step);
+        if !seen.insert(position) {
+            unmake(&mut position, step, undo);
+            continue;
+        }
+        nodes += 1;
+
+        if is_won(&position) {
+            let mut line: Vec<Step> = stack
+                .iter()
+                .filter_map(|frame| frame.made.map(|(step, _)| step))
+                .collect();
+            line.push(step);
+            return Report {
+                verdict: Verdict::Solved(line),
+                nodes,
+            };
+        }
+        if nodes >= limit {
+            return Report {
+                verdict: Verdict::Unknown,
+                nodes,
+            };
+        }
+
+        stack.push(Frame {
+            steps: steps(&position, rules),
+            next: 0,
+            made: Some((step, undo)),
+        });
+    }
+
+    Report {
+        verdict: Verdict::Unsolvable,
+        nodes,
+    }
+}
+
+fn is_won(position: &Packed) -> bool {
+    position.stock_len() == 0 && (0..position.width()).all(|i| position.column(i).is_empty())
+}
+
+fn make(position: &mut Packed, rules: &dyn Rules, step: Step) -> Undo {
+    match step {
+        Step::Move(m) => position.make_move(rules, &m),
+        // steps only offers a deal that can be made
+        Step::Deal => position.deal(rules).unwrap(),
+    }
+}
+
+fn unmake(position: &mut Packed, step: Step, undo: Undo) {
+    match step {
+        Step::Move(m) => position.unmake_move(&m, undo),
+        Step::Deal => position.undeal(undo),
+    }
+}
+
+// steps lists the steps worth trying, most promising first: moves that
+// turn up a hidden card, then moves onto a card of the same suit, then
+// the rest, then the deal
+fn steps(position: &Packed, rules: &dyn Rules) -> Vec<Step> {
+    let width = position.width();
+    let mut ranked: Vec<(u8, Move)> = Vec::new();
+    for orig_col in 0..width {
+        let column = position.column(orig_col);
+        let visible = column.len() - position.hidden(orig_col);
+        for count in 1..=visible {
+            for dest_col in 0..width {
+                let m = Move {
+                    orig_col,
+                    count,
+                    dest_col,
+                };
+                if !position.is_move_valid(rules, &m) {
+                    continue;
+                }
+                let dest = position.column(dest_col);
+                // moving a whole column to an empty one gets nowhere
+                if dest.is_empty() && count == column.len() {
+                    continue;
+                }
+                let moving = column[column.len() - count];
+                let rank = if count == visible && position.hidden(orig_col) > 0 {
+                    0
+                } else if dest.last().is_some_and(|d| d / 13 == moving / 13) {
+                    1
+                } else if !dest.is_empty() {
+                    2
+                } else {
+                    3
+                };
+                ranked.push((rank, m));
+            }
+        }
+    }
+    ranked.sort_by_key(|(rank, _)| *rank);
+
+    let mut steps: Vec<Step> = ranked.into_iter().map(|(_, m)| Step::Move(m)).collect();
+    let mut dealt = *position;
+    if dealt.deal(rules).is_some() {
+        steps.push(Step::Deal);
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::Position;
+
+    fn solve_text(text: &str, limit: usize) -> Report {
+        let position: Position = text.parse().unwrap();
+        solve(
+            &Packed::from_position(&position).unwrap(),
+            &crate::rules::Spider,
+            limit,
+        )
+    }
+
+    // one move from completing the last run
+    const ALMOST_WON: &str = "\
+stock:
+completed: S S H H C C D
+column: KD QD JD XD 9D 8D 7D 6D 5D 4D 3D 2D
+column: AD
+column:
+column:
+column:
+column:
+column:
+column:
+column:
+column:
+";
+
+    #[test]
+    fn solves_and_replays() {
+        let report = solve_text(ALMOST_WON, 1000);
+        let line = match report.verdict {
+            Verdict::Solved(line) => line,
+            verdict => panic!("expected a solution, got {:?}", verdict),
+        };
+        assert_eq!(
+            line,
+            vec![Step::Move(Move {
+                orig_col: 1,
+                count: 1,
+                dest_col: 0
+            })]
+        );
+
+        // the line wins the real game too
+        let mut game = crate::Game::from_position(ALMOST_WON.parse().unwrap()).unwrap();
+        for step in line {
+            match step {
+                Step::Move(m) => game.move_cards(m).unwrap(),
+                Step::Deal => game.deal().unwrap(),
+            };
+        }
+        assert!(game.is_won());
+    }
+
+    #[test]
+    fn unsolvable() {
+        // every visible card is even and every odd card is hidden
+        let text = "\
+stock:
+completed: S S H H C C
+column: [AD] [3D] 2D
+column: [AD] [3D] 2D
+column: [QD] [5D] 4D
+column: [QD] [5D] 4D
+column: [KD] [7D] 6D
+column: [KD] [7D] 6D
+column: [9D] 8D
+column: [9D] 8D
+column: [JD] XD
+column: [JD] XD
+";
+        let report = solve_text(text, 1000);
+        assert_eq!(report.verdict, Verdict::Unsolvable);
+        assert_eq!(report.nodes, 0);
+    }
+
+    #[test]
+    fn gives_up_at_the_limit() {
+        let game =
+            crate::Game::from_seed("v1-0123456789abcdef0123456789abcdef".parse().unwrap()).unwrap();
+        let report = solve(&Packed::from_game(&game).unwrap(), game.rules(), 50);
+        assert_eq!(report.verdict, Verdict::Unknown);
+        assert_eq!(report.nodes, 50);
+    }
+}
@@ -1,35 +1,59 @@
-use rand::{Rng, SeedableRng, XorShiftRng};
-
 use super::error;
+use super::layout::Layout;
 use super::seed;
+use super::shuffle;
 
 use error::GameError::*;
 
 #[derive(Debug, Default)]
 pub struct Source {
-    seed: [u8; 16],
+    seed: Option<seed::Seed>,
     cards: Vec<cards::Card>,
     next_card: usize,
 }
 
 impl Source {
-    pub fn new() -> Source {
+    pub fn new(layout: &Layout) -> Source {
         let seed = seed::from_random();
-        Source::from_seed(seed)
+        Source::from_seed(layout, seed)
     }
 
-    pub fn from_seed(seed: [u8; 16]) -> Source {
-        let mut cards: Vec<cards::Card> = cards::Card::iter().chain(cards::Card::iter()).collect();
-        let mut rng = XorShiftRng::from_seed(seed);
-        rng.shuffle(&mut cards);
+    pub fn from_seed(layout: &Layout, seed: seed::Seed) -> Source {
+        let mut deck = layout.deck();
+        shuffle::shuffle(&mut deck, seed);
         Source {
-            seed,
+            seed: Some(seed),
+            cards: deck.into_cards(),
+            next_card: 0,
+        }
+    }
+
+    // from_deck deals the cards in the order given, first card first
+    pub fn from_deck(layout: &Layout, cards: Vec<cards::Card>) -> Result<Source, error::GameError> {
+        layout.validate_deck(&cards)?;
+        Ok(Source {
+            seed: None,
             cards,
             next_card: 0,
+        })
+    }
+
+    // from_stock deals the undealt cards of a position in the order given,
+    // counting the cards already on the table as dealt; the caller is
+    // responsible for validating the position as a whole
+    pub fn from_stock(dealt: Vec<cards::Card>, stock: Vec<cards::Card>) -> Source {
+        let next_card = dealt.len();
+        let mut cards = dealt;
+        cards.extend(stock);
+        Source {
+            seed: None,
+            cards,
+            next_card,
         }
     }
 
-    pub fn seed(&self) -> [u8; 16] {
+    // seed is None for a source built from an explicit deck
+    pub fn seed(&self) -> Option<seed::Seed> {
         self.seed
     }
 
@@ -46,6 +70,11 @@ impl Source {
         }
     }
 
+    // remaining lists the cards that have not been dealt yet, in deal order
+    pub fn remaining(&self) -> Vec<cards::Card> {
+        self.cards[self.next_card..].to_vec()
+    }
+
     pub fn total_cards(&self) -> usize {
         self.cards.len()
     }
@@ -66,3 +95,14 @@ impl Source {
         }
     }
 }
+
+// standard_deck is the two complete decks of Spider in suit and rank order
+pub fn standard_deck() -> Vec<cards::Card> {
+    Layout::spider().deck().into_cards()
+}
+
+// validate_deck checks that the cards are exactly the standard deck
+// in some order
+pub fn validate_deck(deck: &[cards::Card]) -> Result<(), error::GameError> {
+    Layout::spider().validate_deck(deck)
+}
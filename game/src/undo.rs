@@ -0,0 +1,125 @@
+// undo modes set how far a game may be taken back, for competitive play.
+// A game chooses its mode when it is created:
+//
+//     unlimited   undo as often as you like (the default)
+//     limit:<n>   undo at most n times in the game
+//     cost:<n>    undo as often as you like, for n (0 or more) points each
+//     none        no undo at all
+//
+// The game enforces the limits; scoring is left to the client, which
+// charges cost() for each undo.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use super::error::GameError;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UndoMode {
+    #[default]
+    Unlimited,
+    Limit(usize),
+    Cost(u32),
+    None,
+}
+
+impl UndoMode {
+    // check says whether another undo is allowed after undos of them
+    pub fn check(&self, undos: usize) -> Result<(), GameError> {
+        match self {
+            UndoMode::None => Err(GameError::UndoNotAllowed {}),
+            UndoMode::Limit(limit) if undos >= *limit => {
+                Err(GameError::UndoLimitReached { limit: *limit })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // remaining is the number of undos left after undos of them,
+    // or None if there is no limit
+    pub fn remaining(&self, undos: usize) -> Option<usize> {
+        match self {
+            UndoMode::Limit(limit) => Some(limit.saturating_sub(undos)),
+            UndoMode::None => Some(0),
+            _ => None,
+        }
+    }
+
+    // cost is the points an undo takes off the score
+    pub fn cost(&self) -> i64 {
+        match self {
+            UndoMode::Cost(points) => i64::from(*points),
+            _ => 1,
+        }
+    }
+}
+
+impl fmt::Display for UndoMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UndoMode::Unlimited => write!(f, "unlimited"),
+            UndoMode::Limit(limit) => write!(f, "limit:{}", limit),
+            UndoMode::Cost(points) => write!(f, "cost:{}", points),
+            UndoMode::None => write!(f, "none"),
+        }
+    }
+}
+
+impl FromStr for UndoMode {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<UndoMode, GameError> {
+        let unknown = || GameError::UnknownUndoMode {
+            name: s.to_string(),
+        };
+        match s.split_once(':') {
+            None if s == "unlimited" => Ok(UndoMode::Unlimited),
+            None if s == "none" => Ok(UndoMode::None),
+            Some(("limit", n)) => Ok(UndoMode::Limit(n.parse().map_err(|_| unknown())?)),
+            Some(("cost", n)) => Ok(UndoMode::Cost(n.parse().map_err(|_| unknown())?)),
+            _ => Err(unknown()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        for mode in [
+            UndoMode::Unlimited,
+            UndoMode::Limit(3),
+            UndoMode::Cost(50),
+            UndoMode::None,
+        ] {
+            assert_eq!(mode.to_string().parse::<UndoMode>().unwrap(), mode);
+        }
+        assert!("limit".parse::<UndoMode>().is_err());
+        assert!("limit:x".parse::<UndoMode>().is_err());
+        assert!("cost:-100".parse::<UndoMode>().is_err());
+        assert!("sometimes".parse::<UndoMode>().is_err());
+    }
+
+    #[test]
+    fn check() {
+        assert!(UndoMode::Unlimited.check(1000).is_ok());
+        assert!(UndoMode::Cost(10).check(1000).is_ok());
+        assert!(UndoMode::Limit(2).check(1).is_ok());
+        assert!(matches!(
+            UndoMode::Limit(2).check(2),
+            Err(GameError::UndoLimitReached { limit: 2 })
+        ));
+        assert!(matches!(
+            UndoMode::None.check(0),
+            Err(GameError::UndoNotAllowed {})
+        ));
+        assert_eq!(UndoMode::Limit(2).remaining(1), Some(1));
+        assert_eq!(UndoMode::Unlimited.remaining(1), None);
+        assert_eq!(UndoMode::Cost(10).cost(), 10);
+        assert_eq!(UndoMode::Limit(2).cost(), 1);
+    }
+}
@@ -1,60 +1,169 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use std::str::FromStr;
 use std::{thread, time};
 
 use super::display;
+use super::stats::Outcome;
 
-pub fn play(client: &mut client::Client) -> Result<()> {
-    let mut play = 0;
-    let mut prev_move = (0, 0, 0);
+// a game that goes on this long is going round in circles
+const MAX_PLAYS: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strategy {
+    // the first move that is neither a repeat nor a reversal
+    #[default]
+    Auto,
+    // prefer moves that turn up a hidden card, then moves onto the same suit
+    Greedy,
+}
+
+impl Strategy {
+    // name identifies this strategy in the statistics
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strategy::Auto => "auto",
+            Strategy::Greedy => "greedy",
+        }
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Strategy> {
+        match s {
+            "auto" => Ok(Strategy::Auto),
+            "greedy" => Ok(Strategy::Greedy),
+            _ => bail!("unknown strategy {}: expected auto or greedy", s),
+        }
+    }
+}
+
+// Play is what the strategy did with one turn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Play {
+    Move(game::Move),
+    Deal,
+    Finished(Outcome),
+}
+
+// Player remembers enough of the game to avoid undoing its last move
+#[derive(Debug)]
+pub struct Player {
+    strategy: Strategy,
+    prev_move: Option<game::Move>,
+    plays: usize,
+}
 
-    'play_loop: loop {
+impl Player {
+    pub fn new(strategy: Strategy) -> Player {
+        Player {
+            strategy,
+            prev_move: None,
+            plays: 0,
+        }
+    }
+
+    // play takes one turn: a move, or a deal when there is no move worth making
+    pub fn play(&mut self, client: &mut client::Client) -> Result<Play> {
+        if client.is_won() {
+            return Ok(Play::Finished(Outcome::Won));
+        }
+        self.plays += 1;
+        if self.plays > MAX_PLAYS {
+            return Ok(Play::Finished(Outcome::Lost));
+        }
+
+        let prev_move = self.prev_move;
+        let mut moves: Vec<game::Move> = client
+            .possible_moves()?
+            .into_iter()
+            .filter(|pmv| !pmv.is_used)
+            .map(|pmv| pmv.mv)
+            .filter(|m| match prev_move {
+                None => true,
+                Some(prev) => {
+                    *m != prev
+                        && !(m.orig_col == prev.dest_col
+                            && m.dest_col == prev.orig_col
+                            && m.count == prev.count)
+                }
+            })
+            .collect();
+        if self.strategy == Strategy::Greedy {
+            // a stable sort keeps the client's order among equals
+            moves.sort_by_key(|m| preference(client, m));
+        }
+
+        if let Some(m) = moves.first() {
+            client.move_cards(*m)?;
+            self.prev_move = Some(*m);
+            return Ok(Play::Move(*m));
+        }
+        if client.cards_dealt() == client.total_cards() || client.deal().is_err() {
+            return Ok(Play::Finished(Outcome::Lost));
+        }
+        Ok(Play::Deal)
+    }
+}
+
+// preference ranks a move for the greedy strategy, lowest first
+fn preference(client: &client::Client, m: &game::Move) -> u8 {
+    let orig = &client.local[m.orig_col];
+    let dest = &client.local[m.dest_col];
+    let moving = orig[orig.len() - m.count];
+    let uncovers = orig.len() > m.count && orig[orig.len() - m.count - 1].is_none();
+    let same_suit = match (dest.last(), moving) {
+        (Some(Some(d)), Some(c)) => d.suit == c.suit,
+        _ => false,
+    };
+    match (uncovers, same_suit) {
+        (true, _) => 0,
+        (false, true) => 1,
+        _ if dest.is_empty() => 3,
+        _ => 2,
+    }
+}
+
+// play moves until the game is won or the cards run out,
+// showing the board after every turn
+pub fn play(
+    client: &mut client::Client,
+    strategy: Strategy,
+    style: &cards::render::Style,
+    delay: u64,
+) -> Result<Outcome> {
+    let mut player = Player::new(strategy);
+    let mut play = 0;
+    loop {
         play += 1;
         println!("play: {}", play);
 
-        let moves = client.possible_moves()?;
-        if moves.is_empty() {
-            if client.cards_dealt() == client.total_cards() {
-                println!("cards exhausted");
-                break 'play_loop;
-            } else {
-                println!(">>> dealing");
-                client.deal()?;
-            }
-        } else {
-            let mut moved = false;
-            'move_loop: for (i, pmv) in moves.iter().enumerate() {
-                println!(">>> {:?}[{}]", pmv, i);
-                let current_move = (pmv.mv.orig_col, pmv.mv.count, pmv.mv.dest_col);
-                if current_move == prev_move {
-                    println!("*** duplicate move");
-                    continue 'move_loop;
-                };
-                let current_move_reversed = (pmv.mv.dest_col, pmv.mv.count, pmv.mv.orig_col);
-                if current_move_reversed == prev_move {
-                    println!("*** move cycle");
-                    continue 'move_loop;
-                };
-                if pmv.is_used {
-                    println!("*** move already used");
-                    continue 'move_loop;
+        match player.play(client)? {
+            Play::Finished(outcome) => {
+                match outcome {
+                    Outcome::Won => println!("game won: score {}", client.score()),
+                    _ => println!("cards exhausted"),
                 }
-                client.move_cards(pmv.mv)?;
-                prev_move = current_move;
-                moved = true;
-                break 'move_loop;
-            }
-            if !moved {
-                println!(">>> dealing (unable to find valid move)");
-                client.deal()?;
+                return Ok(outcome);
             }
+            Play::Move(m) => println!(">>> {:?}", m),
+            Play::Deal => println!(">>> dealing"),
         }
 
-        display::local_game(client);
+        display::local_game(client, style);
         display::possible_moves(client)?;
 
-        let wait_time = time::Duration::from_secs(2);
-        thread::sleep(wait_time);
+        thread::sleep(time::Duration::from_secs(delay));
     }
+}
 
-    Ok(())
+// finish plays the game out without showing anything
+pub fn finish(client: &mut client::Client, strategy: Strategy) -> Result<Outcome> {
+    let mut player = Player::new(strategy);
+    loop {
+        if let Play::Finished(outcome) = player.play(client)? {
+            return Ok(outcome);
+        }
+    }
 }
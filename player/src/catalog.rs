@@ -0,0 +1,274 @@
+// catalog classifies deals by how hard they are, so a player can ask for
+// an easy, medium or hard game. Each deal is played out by every strategy
+// and searched by the solver, and rated:
+//
+//     easy     a strategy wins it, or the solver does within a hundredth
+//              of its search limit
+//     medium   the solver wins it
+//     hard     the solver gives up before finding out
+//
+// Deals the solver proves unwinnable get no rating and are never picked.
+// The catalog is one JSON entry per line, appended as deals are rated.
+
+use anyhow::{bail, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use super::auto::{self, Strategy};
+use super::config::Config;
+use super::stats::Outcome;
+
+pub const CATALOG_FILE: &str = "catalog.jsonl";
+
+const STRATEGIES: [Strategy; 2] = [Strategy::Auto, Strategy::Greedy];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Medium => write!(f, "medium"),
+            Difficulty::Hard => write!(f, "hard"),
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Difficulty> {
+        match s {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            _ => bail!("unknown difficulty {}: expected easy, medium or hard", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub seed: String,
+    pub variant: String,
+    pub empty_columns: game::rules::EmptyColumns,
+    // None when the solver gave up before finding out
+    pub solvable: Option<bool>,
+    // the positions the solver searched
+    pub nodes: usize,
+    // the names of the strategies that won the deal
+    pub won_by: Vec<String>,
+    // None for a deal that cannot be won
+    pub difficulty: Option<Difficulty>,
+}
+
+// rate plays the deal of the config's seed with every strategy
+// and searches it with the solver
+pub fn rate(config: &Config, limit: usize) -> Result<Entry> {
+    let seed: game::seed::Seed = match &config.seed {
+        Some(seed) => seed.parse()?,
+        None => game::seed::from_random(),
+    };
+    let hex_seed = seed.to_string();
+
+    let mut won_by: Vec<String> = Vec::new();
+    for strategy in STRATEGIES {
+        let mut client = client::Client::from_layout(config.layout.clone(), Some(&hex_seed))?;
+        if auto::finish(&mut client, strategy)? == Outcome::Won {
+            won_by.push(strategy.name().to_string());
+        }
+    }
+
+    let game = game::Game::from_layout_seed(config.layout.clone(), seed)?;
+    let report = game::solver::solve(
+        &game::packed::Packed::from_game(&game)?,
+        game.rules(),
+        limit,
+    );
+    let solvable = match report.verdict {
+        game::solver::Verdict::Solved(_) => Some(true),
+        game::solver::Verdict::Unsolvable => Some(false),
+        game::solver::Verdict::Unknown => None,
+    };
+    let difficulty = classify(solvable, report.nodes, limit, !won_by.is_empty());
+
+    Ok(Entry {
+        seed: hex_seed,
+        variant: config.layout.name.clone(),
+        empty_columns: config.layout.empty_columns,
+        solvable,
+        nodes: report.nodes,
+        won_by,
+        difficulty,
+    })
+}
+
+// classify rates a deal from the solver's verdict and search, with the
+// search limit, and whether a strategy won it
+fn classify(solvable: Option<bool>, nodes: usize, limit: usize, won: bool) -> Option<Difficulty> {
+    match solvable {
+        Some(false) => None,
+        _ if won => Some(Difficulty::Easy),
+        Some(true) if nodes <= limit / 100 => Some(Difficulty::Easy),
+        Some(true) => Some(Difficulty::Medium),
+        None => Some(Difficulty::Hard),
+    }
+}
+
+pub fn append<P: AsRef<Path>>(path: P, entry: &Entry) -> Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<Entry>> {
+    if !path.as_ref().exists() {
+        return Ok(Vec::new());
+    }
+    let mut entries: Vec<Entry> = Vec::new();
+    for line in BufReader::new(std::fs::File::open(path)?).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            entries.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(entries)
+}
+
+// build rates random deals, or just the one given by --seed,
+// adding them to the catalog
+pub fn build<P: AsRef<Path>>(path: P, config: &Config, games: usize, limit: usize) -> Result<()> {
+    let games = if config.seed.is_some() { 1 } else { games };
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for _ in 0..games {
+        let entry = rate(config, limit)?;
+        let rating = entry
+            .difficulty
+            .map_or("unwinnable".to_string(), |d| d.to_string());
+        println!(
+            "{}: {}; positions searched: {}; won by: {}",
+            entry.seed,
+            rating,
+            entry.nodes,
+            if entry.won_by.is_empty() {
+                "-".to_string()
+            } else {
+                entry.won_by.join(", ")
+            }
+        );
+        *counts.entry(rating).or_default() += 1;
+        append(&path, &entry)?;
+    }
+    println!();
+    let counts: Vec<String> = counts
+        .iter()
+        .map(|(rating, n)| format!("{}: {}", rating, n))
+        .collect();
+    println!(
+        "variant: {}; games: {}; {}",
+        config.layout.name,
+        games,
+        counts.join("; ")
+    );
+    Ok(())
+}
+
+// pick is the seed of a deal of the difficulty, chosen at random from
+// those in the catalog for the variant and house rule
+pub fn pick<P: AsRef<Path>>(
+    path: P,
+    layout: &game::layout::Layout,
+    difficulty: Difficulty,
+) -> Result<String> {
+    let entries: Vec<Entry> = load(&path)?
+        .into_iter()
+        .filter(|e| {
+            e.difficulty == Some(difficulty)
+                && e.variant == layout.name
+                && e.empty_columns == layout.empty_columns
+        })
+        .collect();
+    if entries.is_empty() {
+        bail!(
+            "no {} deals of {} in {}: add some with player catalog",
+            difficulty,
+            layout.name,
+            path.as_ref().display()
+        );
+    }
+    let i = rand::thread_rng().gen_range(0, entries.len());
+    Ok(entries[i].seed.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_deals() {
+        assert_eq!(classify(Some(false), 10, 1000, false), None);
+        assert_eq!(
+            classify(Some(true), 5000, 10000, true),
+            Some(Difficulty::Easy)
+        );
+        assert_eq!(classify(None, 10000, 10000, true), Some(Difficulty::Easy));
+        assert_eq!(
+            classify(Some(true), 100, 10000, false),
+            Some(Difficulty::Easy)
+        );
+        assert_eq!(
+            classify(Some(true), 101, 10000, false),
+            Some(Difficulty::Medium)
+        );
+        assert_eq!(classify(None, 10000, 10000, false), Some(Difficulty::Hard));
+    }
+
+    #[test]
+    fn pick_filters() {
+        let path = std::env::temp_dir().join(format!("catalog-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let entry = |seed: &str, variant: &str, empty_columns, difficulty| Entry {
+            seed: seed.to_string(),
+            variant: variant.to_string(),
+            empty_columns,
+            solvable: Some(true),
+            nodes: 0,
+            won_by: Vec::new(),
+            difficulty,
+        };
+        use game::rules::EmptyColumns::*;
+        for e in [
+            entry("a", "spider", Strict, Some(Difficulty::Easy)),
+            entry("b", "spider", Fill, Some(Difficulty::Medium)),
+            entry("c", "spider-1-suit", Strict, Some(Difficulty::Medium)),
+            entry("d", "spider", Strict, Some(Difficulty::Medium)),
+            entry("e", "spider", Strict, None),
+        ] {
+            append(&path, &e).unwrap();
+        }
+
+        let mut layout = game::layout::Layout::spider();
+        assert_eq!(pick(&path, &layout, Difficulty::Medium).unwrap(), "d");
+        assert_eq!(pick(&path, &layout, Difficulty::Easy).unwrap(), "a");
+        assert!(pick(&path, &layout, Difficulty::Hard).is_err());
+        layout.empty_columns = Fill;
+        assert_eq!(pick(&path, &layout, Difficulty::Medium).unwrap(), "b");
+        assert!(pick(&path, &layout, Difficulty::Easy).is_err());
+        let layout = game::layout::Layout::spider_one_suit();
+        assert_eq!(pick(&path, &layout, Difficulty::Medium).unwrap(), "c");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
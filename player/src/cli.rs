@@ -0,0 +1,114 @@
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+/// Spider (2) Solitaire
+///
+/// Flags left off the command line come from the config file,
+/// player.toml in the current directory unless --config names another.
+#[derive(Debug, Parser)]
+#[command(name = "player", version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// seed of the game to deal, as hex or v1-<hex>; random when omitted
+    #[arg(long, global = true)]
+    pub seed: Option<String>,
+
+    /// deal a game of this difficulty from the catalog: easy, medium or hard
+    #[arg(long, global = true, conflicts_with = "seed")]
+    pub difficulty: Option<String>,
+
+    /// game variant: spider, spider-2-suit, spider-1-suit, spider8, spiderette,
+    /// will-o-the-wisp, scorpion, or one defined in the config file
+    #[arg(long, global = true)]
+    pub variant: Option<String>,
+
+    /// house rule for dealing while a column is empty: strict (no deal),
+    /// fill (deal onto empty columns too) or skip (deal only onto the others)
+    #[arg(long, global = true)]
+    pub empty_columns: Option<String>,
+
+    /// undo mode: unlimited, limit:<n> undos a game, cost:<n> points an undo,
+    /// or none
+    #[arg(long, global = true)]
+    pub undo: Option<String>,
+
+    /// strategy for auto, simulate and the auto command: auto or greedy
+    #[arg(long, global = true)]
+    pub strategy: Option<String>,
+
+    /// card style: plain, unicode, glyph, color, or <style>+color
+    #[arg(long, global = true)]
+    pub style: Option<String>,
+
+    /// show cards without color, whatever the style
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// file of command history kept between sessions
+    #[arg(long, global = true)]
+    pub history_file: Option<PathBuf>,
+
+    /// stream the game to watchers under this game id
+    #[arg(long, global = true)]
+    pub broadcast: Option<String>,
+
+    /// config file of defaults for these flags
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// play a game at the prompt (the default)
+    Play {
+        /// run the commands in a file, or - for stdin, without a prompt
+        #[arg(long)]
+        script: Option<String>,
+    },
+    /// watch a strategy play a game
+    Auto,
+    /// play back a file of player commands, showing the board at each step
+    Replay { file: PathBuf },
+    /// play many games with a strategy and report how it did
+    Simulate {
+        /// number of games to play
+        #[arg(long, default_value_t = 100)]
+        games: usize,
+    },
+    /// search for a winning line of play, seeing every card
+    Solve {
+        /// number of positions to search before giving up
+        #[arg(long, default_value_t = 1_000_000)]
+        limit: usize,
+    },
+    /// rate deals by difficulty, adding them to the catalog
+    Catalog {
+        /// number of deals to rate
+        #[arg(long, default_value_t = 100)]
+        games: usize,
+        /// number of positions the solver searches before giving up
+        #[arg(long, default_value_t = 100_000)]
+        limit: usize,
+    },
+    /// race several players through the same deal, taking turns at the prompt
+    Race {
+        /// the players' names, separated by commas
+        #[arg(long, value_delimiter = ',', num_args = 1.., required = true)]
+        players: Vec<String>,
+    },
+    /// play the daily deal, the same for everyone on the day
+    Daily {
+        /// the day of the deal, as YYYY-MM-DD; today when omitted
+        #[arg(long)]
+        date: Option<String>,
+        /// show the day's leaderboard without playing
+        #[arg(long)]
+        leaderboard: bool,
+    },
+    /// watch the game broadcast under a game id, or on stdin for -
+    Watch { game_id: String },
+    /// show the statistics of recorded games
+    Stats,
+}
@@ -0,0 +1,107 @@
+use anyhow::{anyhow, bail, Result};
+use std::str::FromStr;
+
+// Command is one line of player input, typed at the prompt or read from a script
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Help,
+    Quit,
+    Deal,
+    // the number of one of the possible moves, counting from 1
+    Move(usize),
+    // an explicit move: origin column, number of cards, destination column
+    MoveCards(game::Move),
+    Undo,
+    Pause,
+    Resume,
+    Checkpoints,
+    Auto,
+    // report commands for scripts
+    Moves,
+    Status,
+    Board,
+    // the whole state, as client::state::State in JSON
+    Json,
+}
+
+impl FromStr for Command {
+    type Err = anyhow::Error;
+
+    fn from_str(line: &str) -> Result<Command> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let command = match words.as_slice() {
+            ["help"] => Command::Help,
+            ["quit"] => Command::Quit,
+            ["deal"] => Command::Deal,
+            ["move"] => bail!("you must specify the number of a move"),
+            ["move", n] => Command::Move(
+                n.parse()
+                    .map_err(|e| anyhow!("invalid move number {}: {}", n, e))?,
+            ),
+            ["move", orig_col, count, dest_col] => {
+                let number = |s: &str| {
+                    s.parse::<usize>()
+                        .map_err(|e| anyhow!("invalid move {}: {}", line.trim(), e))
+                };
+                Command::MoveCards(game::Move {
+                    orig_col: number(orig_col)?,
+                    count: number(count)?,
+                    dest_col: number(dest_col)?,
+                })
+            }
+            ["undo"] => Command::Undo,
+            ["pause"] => Command::Pause,
+            ["resume"] => Command::Resume,
+            ["checkpoints"] => Command::Checkpoints,
+            ["auto", ..] => Command::Auto,
+            ["moves"] => Command::Moves,
+            ["status"] => Command::Status,
+            ["board"] => Command::Board,
+            ["json"] => Command::Json,
+            _ => bail!("invalid input: {}", line.trim()),
+        };
+        Ok(command)
+    }
+}
+
+// play carries out the commands that change the game
+pub fn play(client: &mut client::Client, command: Command) -> Result<()> {
+    match command {
+        Command::Deal => {
+            if client.cards_dealt() == client.total_cards() {
+                bail!("no cards left to deal");
+            }
+            client.deal()?;
+        }
+        Command::Move(n) => {
+            let moves = client.possible_moves()?;
+            if n == 0 || n > moves.len() {
+                bail!("move number {} out of bounds", n);
+            }
+            client.move_cards(moves[n - 1].mv)?;
+        }
+        Command::MoveCards(m) => {
+            if !client.is_move_valid(&m) {
+                bail!("invalid move {:?}", m);
+            }
+            client.move_cards(m)?;
+        }
+        Command::Undo => {
+            if client.checkpoints().len() < 2 {
+                bail!("nothing to undo");
+            }
+            client.undo()?;
+        }
+        Command::Pause => client.pause(),
+        Command::Resume => client.resume(),
+        _ => bail!("{:?} does not change the game", command),
+    }
+    Ok(())
+}
+
+// is_lost is true when there is nothing left to deal and nothing to move
+pub fn is_lost(client: &client::Client) -> Result<bool> {
+    Ok(!client.is_won()
+        && client.cards_dealt() == client.total_cards()
+        && client.possible_moves()?.is_empty())
+}
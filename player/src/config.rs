@@ -0,0 +1,151 @@
+use anyhow::{anyhow, bail, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use super::auto::Strategy;
+use super::catalog::Difficulty;
+use super::cli::Cli;
+
+pub const CONFIG_FILE: &str = "player.toml";
+
+// File is the config file: every entry is optional
+//
+//   style = "unicode+color"
+//   variant = "spider"
+//   empty_columns = "fill"
+//   undo = "limit:3"
+//   strategy = "greedy"
+//   history_file = "history.txt"
+//   no_color = false
+//   delay = 2
+//
+// and may define variants of its own, as game::layout::Layout:
+//
+//   [[layouts]]
+//   name = "spiderette-2-suit"
+//   copies = 2
+//   suits = ["Hearts", "Spades"]
+//   columns = [1, 2, 3, 4, 5, 6, 7]
+//
+// with, optionally, the cards dealt face down in each column and the
+// rules, "spider" or "scorpion":
+//
+//   face_down = [0, 1, 2, 3, 4, 5, 6]
+//   rules = "spider"
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct File {
+    style: Option<String>,
+    variant: Option<String>,
+    empty_columns: Option<String>,
+    undo: Option<String>,
+    strategy: Option<String>,
+    history_file: Option<PathBuf>,
+    no_color: Option<bool>,
+    delay: Option<u64>,
+    #[serde(default)]
+    layouts: Vec<game::layout::Layout>,
+}
+
+// Config is the settings for this run: command line flags,
+// then the config file, then the defaults
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub seed: Option<String>,
+    // the difficulty of deal to pick from the catalog instead of a seed
+    pub difficulty: Option<Difficulty>,
+    pub layout: game::layout::Layout,
+    pub undo_mode: game::undo::UndoMode,
+    pub strategy: Strategy,
+    pub style: cards::render::Style,
+    pub history_file: PathBuf,
+    // seconds to wait between steps when auto playing or replaying
+    pub delay: u64,
+    // the game id to broadcast the game under
+    pub broadcast: Option<String>,
+}
+
+impl Config {
+    pub fn load(cli: &Cli) -> Result<Config> {
+        let file = match &cli.config {
+            Some(path) => read(path)?,
+            None if Path::new(CONFIG_FILE).exists() => read(Path::new(CONFIG_FILE))?,
+            None => File::default(),
+        };
+
+        // the config file's variants come first, so they can replace a builtin
+        let mut layouts = file.layouts;
+        layouts.extend(game::layout::Layout::builtin());
+        let mut layout = match cli.variant.as_ref().or(file.variant.as_ref()) {
+            None => game::layout::Layout::spider(),
+            Some(name) => match layouts.iter().find(|layout| layout.name == *name) {
+                Some(layout) => layout.clone(),
+                None => {
+                    let names: Vec<&str> = layouts.iter().map(|l| l.name.as_str()).collect();
+                    bail!(
+                        "unknown variant {}: expected one of {}",
+                        name,
+                        names.join(", ")
+                    )
+                }
+            },
+        };
+        // the house rule applies to whichever variant is played
+        if let Some(name) = cli.empty_columns.as_ref().or(file.empty_columns.as_ref()) {
+            layout.empty_columns = name.parse()?;
+        }
+        layout.validate()?;
+
+        let undo_mode = match cli.undo.as_ref().or(file.undo.as_ref()) {
+            Some(name) => name.parse()?,
+            None => game::undo::UndoMode::default(),
+        };
+
+        let strategy = match cli.strategy.as_ref().or(file.strategy.as_ref()) {
+            Some(name) => name.parse()?,
+            None => Strategy::default(),
+        };
+
+        let mut style = match cli.style.as_ref().or(file.style.as_ref()) {
+            Some(name) => name.parse()?,
+            None => cards::render::Style::default(),
+        };
+        if cli.no_color || file.no_color.unwrap_or(false) {
+            style.color = false;
+        }
+
+        let difficulty = match &cli.difficulty {
+            Some(name) => Some(name.parse()?),
+            None => None,
+        };
+
+        Ok(Config {
+            seed: cli.seed.clone(),
+            difficulty,
+            layout,
+            undo_mode,
+            strategy,
+            style,
+            history_file: cli
+                .history_file
+                .clone()
+                .or(file.history_file)
+                .unwrap_or_else(|| PathBuf::from("history.txt")),
+            delay: file.delay.unwrap_or(2),
+            broadcast: cli.broadcast.clone(),
+        })
+    }
+
+    pub fn client(&self) -> Result<client::Client> {
+        Ok(
+            client::Client::from_layout(self.layout.clone(), self.seed.as_deref())?
+                .with_undo_mode(self.undo_mode),
+        )
+    }
+}
+
+fn read(path: &Path) -> Result<File> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("unable to read config {}: {}", path.display(), e))?;
+    toml::from_str(&text).map_err(|e| anyhow!("invalid config {}: {}", path.display(), e))
+}
@@ -0,0 +1,255 @@
+// daily deals everyone the same game each day, from a seed derived from the
+// date, and keeps a leaderboard of each player's best on the day's deal:
+// one JSON entry per line, per day, variant, house rule, undo mode and
+// player, so only games played by the same rules compete.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use super::stats::{self, Outcome, Record};
+
+pub const LEADERBOARD_FILE: &str = "leaderboard.jsonl";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    // the day of the deal, as YYYY-MM-DD
+    pub day: String,
+    pub variant: String,
+    // entries from before these were kept were strict and unlimited
+    #[serde(default)]
+    pub empty_columns: game::rules::EmptyColumns,
+    #[serde(default)]
+    pub undo: game::undo::UndoMode,
+    pub player: String,
+    pub seed: String,
+    pub games: usize,
+    // None until the player has finished a game of the deal
+    pub best_score: Option<i64>,
+    // seconds, if the player has won the deal
+    pub fastest_win: Option<u64>,
+}
+
+impl Entry {
+    fn is_for(&self, day: &str, record: &Record) -> bool {
+        self.day == day
+            && self.variant == record.variant
+            && self.empty_columns == record.empty_columns
+            && self.undo == record.undo
+            && self.player == record.player
+    }
+
+    fn add(&mut self, record: &Record) {
+        self.games += 1;
+        // an abandoned game's score is only how far it got
+        if record.outcome != Outcome::Abandoned {
+            self.best_score = Some(
+                self.best_score
+                    .map_or(record.score, |s| s.max(record.score)),
+            );
+        }
+        if record.outcome == Outcome::Won {
+            self.fastest_win = Some(
+                self.fastest_win
+                    .map_or(record.seconds, |s| s.min(record.seconds)),
+            );
+        }
+    }
+}
+
+// day is the date given as YYYY-MM-DD, or today's
+pub fn day(date: Option<&str>) -> Result<String> {
+    match date {
+        None => Ok(chrono::Local::now().format("%Y-%m-%d").to_string()),
+        Some(date) => chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .map_err(|e| anyhow!("invalid date {}: expected YYYY-MM-DD: {}", date, e)),
+    }
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<Entry>> {
+    if !path.as_ref().exists() {
+        return Ok(Vec::new());
+    }
+    let mut entries: Vec<Entry> = Vec::new();
+    for line in BufReader::new(std::fs::File::open(path)?).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            entries.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(entries)
+}
+
+fn save<P: AsRef<Path>>(path: P, entries: &[Entry]) -> Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    for entry in entries {
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    }
+    Ok(())
+}
+
+// add keeps the record of a game of the day's deal if it beats
+// the player's best so far
+pub fn add<P: AsRef<Path>>(path: P, day: &str, record: &Record) -> Result<()> {
+    let mut entries = load(&path)?;
+    match entries.iter().position(|e| e.is_for(day, record)) {
+        Some(i) => entries[i].add(record),
+        None => {
+            let mut entry = Entry {
+                day: day.to_string(),
+                variant: record.variant.clone(),
+                empty_columns: record.empty_columns,
+                undo: record.undo,
+                player: record.player.clone(),
+                seed: record.seed.clone(),
+                games: 0,
+                best_score: None,
+                fastest_win: None,
+            };
+            entry.add(record);
+            entries.push(entry);
+        }
+    }
+    save(path, &entries)
+}
+
+// rank puts winners first by time, then the others by score, then those
+// who have yet to finish a game
+fn rank(entries: &mut [Entry]) {
+    entries.sort_by(|a, b| match (a.fastest_win, b.fastest_win) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => b.best_score.cmp(&a.best_score),
+    });
+}
+
+// show prints the day's leaderboard for the layout's variant and house
+// rule, played in the undo mode
+pub fn show<P: AsRef<Path>>(
+    path: P,
+    day: &str,
+    layout: &game::layout::Layout,
+    undo: game::undo::UndoMode,
+) -> Result<()> {
+    let mut entries: Vec<Entry> = load(path)?
+        .into_iter()
+        .filter(|e| {
+            e.day == day
+                && e.variant == layout.name
+                && e.empty_columns == layout.empty_columns
+                && e.undo == undo
+        })
+        .collect();
+    println!(
+        "daily deal of {} ({}; empty columns: {}; undo: {})",
+        day, layout.name, layout.empty_columns, undo
+    );
+    if entries.is_empty() {
+        println!("no games recorded");
+        return Ok(());
+    }
+    rank(&mut entries);
+    for (i, entry) in entries.iter().enumerate() {
+        println!(
+            "{:>3}. {:<12} best score: {:>5}; fastest win: {:>6}; games: {}",
+            i + 1,
+            entry.player,
+            entry.best_score.map_or("-".to_string(), |s| s.to_string()),
+            entry.fastest_win.map_or("-".to_string(), stats::minutes),
+            entry.games
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(player: &str, outcome: Outcome, score: i64, seconds: u64) -> Record {
+        Record {
+            seed: game::seed::daily("2024-05-01").to_string(),
+            variant: "spider".to_string(),
+            empty_columns: game::rules::EmptyColumns::Strict,
+            undo: game::undo::UndoMode::None,
+            outcome,
+            score,
+            moves: 0,
+            seconds,
+            date: "2024-05-01 12:00:00".to_string(),
+            player: player.to_string(),
+        }
+    }
+
+    #[test]
+    fn keeps_the_best() {
+        let path = std::env::temp_dir().join(format!("leaderboard-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        for r in [
+            record("ann", Outcome::Lost, 300, 100),
+            record("ann", Outcome::Won, 600, 500),
+            record("ann", Outcome::Won, 550, 400),
+            record("ann", Outcome::Abandoned, 100, 10),
+            record("ann", Outcome::Abandoned, 900, 10),
+        ] {
+            add(&path, "2024-05-01", &r).unwrap();
+        }
+        let entries = load(&path).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].games, 5);
+        assert_eq!(entries[0].best_score, Some(600));
+        assert_eq!(entries[0].fastest_win, Some(400));
+
+        // other rules, or another day, don't compete with these games
+        let mut unlimited = record("ann", Outcome::Won, 900, 60);
+        unlimited.undo = game::undo::UndoMode::Unlimited;
+        add(&path, "2024-05-01", &unlimited).unwrap();
+        let mut fill = record("ann", Outcome::Won, 900, 60);
+        fill.empty_columns = game::rules::EmptyColumns::Fill;
+        add(&path, "2024-05-01", &fill).unwrap();
+        add(&path, "2024-05-02", &record("ann", Outcome::Won, 900, 60)).unwrap();
+        let entries = load(&path).unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].best_score, Some(600));
+
+        // an abandoned game counts, but sets no score
+        add(
+            &path,
+            "2024-05-01",
+            &record("bob", Outcome::Abandoned, 500, 10),
+        )
+        .unwrap();
+        let entries = load(&path).unwrap();
+        assert_eq!((entries[4].games, entries[4].best_score), (1, None));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn ranking() {
+        let entry = |player: &str, best_score, fastest_win| Entry {
+            day: "2024-05-01".to_string(),
+            variant: "spider".to_string(),
+            empty_columns: game::rules::EmptyColumns::Strict,
+            undo: game::undo::UndoMode::None,
+            player: player.to_string(),
+            seed: String::new(),
+            games: 1,
+            best_score,
+            fastest_win,
+        };
+        let mut entries = vec![
+            entry("ann", Some(400), None),
+            entry("bob", Some(500), Some(300)),
+            entry("ed", None, None),
+            entry("cy", Some(600), None),
+            entry("di", Some(100), Some(200)),
+        ];
+        rank(&mut entries);
+        let order: Vec<&str> = entries.iter().map(|e| e.player.as_str()).collect();
+        assert_eq!(order, vec!["di", "bob", "cy", "ann", "ed"]);
+    }
+}
@@ -1,43 +1,108 @@
 use anyhow::Result;
 
+const COLUMN_WIDTH: usize = 10;
+
 pub fn help() {
     println!("quit: exit game");
     println!("deal: deal one card face up on each pile");
     println!("move <n>:Doug Fort Consulting, Inc. execute one of the numbered moves");
+    println!("move <orig> <count> <dest>: move count cards from column orig to column dest");
     println!("checkpoints: list the known checkpoints");
     println!("undo: undo the previous operation");
+    println!("pause: stop the clock until you press enter");
+    println!("status: show the session statistics");
+    println!("json: show the board, moves, checkpoints and status as JSON");
     println!("auto [<n>]: play in auto mode, for at most 'n' moves");
+    println!();
+    println!("player play --script <file> reads these commands from a file, or - for stdin,");
+    println!("and exits with 0 when the game is won, 1 lost, 2 on error, 3 unfinished;");
+    println!("player --help lists the other ways to run the player");
+}
+
+pub fn race_help() {
+    println!("next: hand the game over to the next player");
+    println!("scores: show the scoreboard");
+    println!("resign: stop playing in this race");
+    println!("quit: end the race for everyone");
+    println!();
+    help();
 }
 
-pub fn local_game(client: &client::Client) {
+pub fn local_game(client: &client::Client, style: &cards::render::Style) {
     println!();
     println!(
-        "game: {}; cards dealt: {}; cards remaining: {}: digest {}",
+        "{}{}: {}; cards dealt: {}; cards remaining: {}: digest {}",
+        client.layout().name,
+        match client.layout().empty_columns {
+            game::rules::EmptyColumns::Strict => "".to_string(),
+            empty_columns => format!(" ({} empty columns)", empty_columns),
+        },
         client.seed(),
         client.cards_dealt(),
         client.total_cards() - client.cards_dealt(),
         client.digest()
     );
+    let session = client.session_stats();
+    println!(
+        "time: {}{}; moves: {}; undos: {}{}; deals: {}; score: {}",
+        super::stats::minutes(session.elapsed.as_secs()),
+        if session.paused { " (paused)" } else { "" },
+        session.moves,
+        session.undos,
+        match client.undos_left() {
+            Some(left) => format!(" ({} left)", left),
+            None => "".to_string(),
+        },
+        session.deals,
+        client.score()
+    );
+    println!();
     println!();
+    columns(&client.local, style);
+}
+
+// spectator shows a watched game, as its broadcast shows it
+pub fn spectator(spectator: &client::spectator::Spectator, style: &cards::render::Style) {
+    let status = &spectator.status;
     println!();
     println!(
-        " {:^10} {:^10} {:^10} {:^10} {:^10} {:^10} {:^10} {:^10} {:^10} {:^10}",
-        0, 1, 2, 3, 4, 5, 6, 7, 8, 9
+        "watching {}: {}; cards remaining: {}",
+        spectator.variant, spectator.seed, status.cards_remaining
+    );
+    println!(
+        "moves: {}; runs: {}; score: {}{}",
+        status.moves,
+        status.completed_runs,
+        status.score,
+        if status.won { "; won" } else { "" }
     );
+    println!();
+    println!();
+    columns(&spectator.local, style);
+}
 
-    let max_col = client.local.iter().map(|col| col.len()).max().unwrap();
+fn columns(local: &[Vec<Option<cards::Card>>], style: &cards::render::Style) {
+    let header = (0..local.len()).fold("".to_string(), |line, i| {
+        format!("{} {:^width$}", line, i, width = COLUMN_WIDTH)
+    });
+    println!("{}", header);
 
+    let max_col = local.iter().map(|col| col.len()).max().unwrap_or(0);
+
+    // pad by the style's width rather than with {:<10}, which would count
+    // color escapes and multi-byte symbols as columns
+    let padding = " ".repeat(COLUMN_WIDTH.saturating_sub(style.width()));
     for y in 0..max_col {
-        let result = client.local.iter().fold("".to_string(), |line, col| {
+        let result = local.iter().fold("".to_string(), |line, col| {
             let entry = if y < col.len() {
                 match col[y] {
-                    Some(card) => format!("{}", card),
-                    None => "(----)".to_string(),
+                    Some(card) => style.card(card),
+                    None => style.hidden(),
                 }
             } else {
-                "          ".to_string()
+                " ".repeat(style.width())
             };
-            format!("{} {:<10}", line, entry)
+            format!("{} {}{}", line, entry, padding)
         });
         println!("{}", result);
     }
@@ -53,3 +118,30 @@ pub fn possible_moves(client: &client::Client) -> Result<()> {
 
     Ok(())
 }
+
+pub fn scoreboard(standings: &[client::race::Standing]) {
+    println!();
+    println!(
+        "{:>4}  {:<12} {:>5} {:>6} {:>7} {:>6}",
+        "rank", "player", "runs", "moves", "time", "score"
+    );
+    for standing in standings {
+        println!(
+            "{:>4}  {:<12} {:>5} {:>6} {:>7} {:>6}{}",
+            standing.rank,
+            standing.name,
+            standing.completed_runs,
+            standing.moves,
+            super::stats::minutes(standing.elapsed.as_secs()),
+            standing.score,
+            if standing.won {
+                " won"
+            } else if standing.done {
+                " out"
+            } else {
+                ""
+            }
+        );
+    }
+    println!();
+}
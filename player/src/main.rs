@@ -1,80 +1,162 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use clap::Parser;
 use rustyline::error::ReadlineError;
 use rustyline::Editor;
+use std::io::IsTerminal;
 
 mod auto;
+mod catalog;
+mod cli;
+mod commands;
+mod config;
+mod daily;
 mod display;
+mod race;
+mod script;
+mod solve;
+mod stats;
+mod watch;
+
+use commands::Command;
+use config::Config;
 
 fn main() -> Result<()> {
-    let mut client = match std::env::args().nth(1) {
-        Some(seed) => client::Client::from_hex(&seed)?,
-        None => client::Client::new()?,
-    };
+    let cli = cli::Cli::parse();
+    let mut config = Config::load(&cli)?;
+
+    // a difficulty picks the deal to play, so only the commands that play
+    // a new deal take one
+    let plays = matches!(
+        cli.command,
+        None | Some(cli::Command::Play { .. })
+            | Some(cli::Command::Auto)
+            | Some(cli::Command::Simulate { .. })
+            | Some(cli::Command::Race { .. })
+    );
+    match config.difficulty {
+        Some(difficulty) if plays => {
+            config.seed = Some(catalog::pick(
+                catalog::CATALOG_FILE,
+                &config.layout,
+                difficulty,
+            )?)
+        }
+        Some(_) => bail!("--difficulty picks a deal to play, so this command can't take it"),
+        None => {}
+    }
+
+    match cli.command {
+        None => play(&config, None),
+        Some(cli::Command::Play { script }) => play(&config, script),
+        Some(cli::Command::Auto) => auto(&config),
+        Some(cli::Command::Replay { file }) => {
+            let mut client = config.client()?;
+            let input = std::fs::File::open(&file)
+                .map_err(|e| anyhow::anyhow!("unable to open {}: {}", file.display(), e))?;
+            script::replay(
+                &mut client,
+                std::io::BufReader::new(input),
+                &config.style,
+                config.delay,
+            )
+        }
+        Some(cli::Command::Simulate { games }) => simulate(&config, games),
+        Some(cli::Command::Solve { limit }) => solve::run(&config, limit),
+        Some(cli::Command::Daily { date, leaderboard }) => {
+            daily(&config, date.as_deref(), leaderboard)
+        }
+        Some(cli::Command::Catalog { games, limit }) => {
+            catalog::build(catalog::CATALOG_FILE, &config, games, limit)
+        }
+        Some(cli::Command::Race { players }) => race::run(&config, &players),
+        Some(cli::Command::Watch { game_id }) => watch::run(&game_id, &config.style),
+        Some(cli::Command::Stats) => stats::show(stats::STATS_FILE),
+    }
+}
+
+fn play(config: &Config, script: Option<String>) -> Result<()> {
+    play_game(config, script).map(|_| ())
+}
+
+// play_game returns the record of the game, unless it was not worth keeping
+fn play_game(config: &Config, mut script: Option<String>) -> Result<Option<stats::Record>> {
+    // piped input runs as a script, just as --script - does
+    if script.is_none() && !std::io::stdin().is_terminal() {
+        script = Some("-".to_string());
+    }
+    if let Some(path) = script {
+        std::process::exit(run_script(config, &path));
+    }
+
+    let mut client = config.client()?;
+    watch::broadcast(config, &mut client)?;
 
     let mut rl = Editor::<()>::new();
-    if rl.load_history("history.txt").is_err() {
+    if rl.load_history(&config.history_file).is_err() {
         println!("No previous history.");
     }
 
+    let mut player = stats::human_name();
+    let mut outcome = stats::Outcome::Abandoned;
+
     loop {
-        display::local_game(&client);
+        if client.is_won() {
+            display::local_game(&client, &config.style);
+            println!("game won: score {}", client.score());
+            outcome = stats::Outcome::Won;
+            break;
+        }
+
+        display::local_game(&client, &config.style);
         display::possible_moves(&client)?;
 
         let readline = rl.readline(">> ");
         match readline {
             Ok(line) => {
                 rl.add_history_entry(line.as_str());
-                let command: Vec<&str> = line.trim().split_whitespace().collect();
-                if command.is_empty() {
+                if line.trim().is_empty() {
                     continue;
                 }
-                match command[0].trim() {
-                    "" => continue,
-                    "help" => display::help(),
-                    "quit" => break,
-                    "deal" => {
-                        if client.cards_dealt() < client.total_cards() {
-                            client.deal()?;
-                        };
+                let command = match line.parse::<Command>() {
+                    Ok(command) => command,
+                    Err(e) => {
+                        println!("{}", e);
+                        continue;
                     }
-                    "move" => {
-                        if command.len() < 2 {
-                            println!("you must specify the number of a move");
-                            continue;
-                        }
-                        match command[1].parse::<usize>() {
-                            Ok(n) => {
-                                let moves = client.possible_moves()?;
-                                if n > moves.len() {
-                                    println!("move number {} out of bounds", n);
-                                    continue;
-                                }
-                                client.move_cards(moves[n - 1].mv)?;
-                            }
-                            Err(e) => {
-                                println!("invalid move number {}", e);
-                                continue;
-                            }
-                        };
+                };
+                match command {
+                    Command::Help => display::help(),
+                    Command::Quit => break,
+                    Command::Pause => {
+                        client.pause();
+                        println!("paused: press enter to resume");
+                        let _ = rl.readline("");
+                        client.resume();
                     }
-                    "checkpoints" => {
+                    Command::Checkpoints => {
                         for cp in client.checkpoints() {
                             println!("{:?}", cp);
                         }
                     }
-                    "undo" => {
-                        if client.checkpoints().len() < 2 {
-                            println!("nothing to undo");
-                            continue;
-                        };
-                        client.undo()?;
+                    Command::Auto => {
+                        // the strategy finishes the game, so it shares the record
+                        player = format!("{}+{}", stats::human_name(), config.strategy.name());
+                        outcome =
+                            auto::play(&mut client, config.strategy, &config.style, config.delay)?;
+                        if outcome != stats::Outcome::Abandoned {
+                            break;
+                        }
                     }
-                    "auto" => {
-                        auto::play(&mut client)?;
+                    // the board and moves are shown before every prompt
+                    Command::Moves | Command::Board => continue,
+                    Command::Status => println!("{:?}", client.session_stats()),
+                    Command::Json => {
+                        println!("{}", serde_json::to_string_pretty(&client.state()?)?)
                     }
                     _ => {
-                        println!("invalid input");
-                        continue;
+                        if let Err(e) = commands::play(&mut client, command) {
+                            println!("{}", e);
+                        }
                     }
                 }
             }
@@ -93,5 +175,113 @@ fn main() -> Result<()> {
         }
     }
 
+    client.end_broadcast()?;
+    if let Err(e) = rl.save_history(&config.history_file) {
+        println!("unable to save history: {}", e);
+    }
+
+    // a game that was never played is not worth recording
+    if client.checkpoints().len() > 1 || outcome != stats::Outcome::Abandoned {
+        let record = stats::Record::new(&client, outcome, &player);
+        stats::append(stats::STATS_FILE, &record)?;
+        return Ok(Some(record));
+    }
+
+    Ok(None)
+}
+
+// run_script returns the script's exit code, which must say how the game
+// went, so a failure to start the game is an error rather than a loss
+fn run_script(config: &Config, path: &str) -> i32 {
+    let mut client = match config.client() {
+        Ok(client) => client,
+        Err(e) => {
+            println!("error {}", e);
+            return script::ERROR;
+        }
+    };
+    if let Err(e) = watch::broadcast(config, &mut client) {
+        println!("error {}", e);
+        return script::ERROR;
+    }
+    let code = if path == "-" {
+        script::run(&mut client, std::io::stdin().lock())
+    } else {
+        match std::fs::File::open(path) {
+            Ok(file) => script::run(&mut client, std::io::BufReader::new(file)),
+            Err(e) => {
+                println!("error unable to open script {}: {}", path, e);
+                script::ERROR
+            }
+        }
+    };
+    // watchers going away doesn't change how the game went
+    let _ = client.end_broadcast();
+    code
+}
+
+// daily plays the day's deal and shows the day's leaderboard
+fn daily(config: &Config, date: Option<&str>, leaderboard: bool) -> Result<()> {
+    let day = daily::day(date)?;
+    if !leaderboard {
+        if config.seed.is_some() {
+            bail!("the daily deal has a seed of its own: drop --seed");
+        }
+        let config = Config {
+            seed: Some(game::seed::daily(&day).to_string()),
+            ..config.clone()
+        };
+        println!("daily deal of {}", day);
+        if let Some(record) = play_game(&config, None)? {
+            daily::add(daily::LEADERBOARD_FILE, &day, &record)?;
+        }
+    }
+    daily::show(
+        daily::LEADERBOARD_FILE,
+        &day,
+        &config.layout,
+        config.undo_mode,
+    )
+}
+
+fn auto(config: &Config) -> Result<()> {
+    let mut client = config.client()?;
+    watch::broadcast(config, &mut client)?;
+    display::local_game(&client, &config.style);
+    let outcome = auto::play(&mut client, config.strategy, &config.style, config.delay)?;
+    client.end_broadcast()?;
+    let record = stats::Record::new(&client, outcome, config.strategy.name());
+    stats::append(stats::STATS_FILE, &record)
+}
+
+// simulate plays random deals, or just the one given by --seed,
+// without recording them in the statistics
+fn simulate(config: &Config, games: usize) -> Result<()> {
+    let games = if config.seed.is_some() { 1 } else { games };
+    let mut won = 0;
+    for _ in 0..games {
+        let mut client = config.client()?;
+        let outcome = auto::finish(&mut client, config.strategy)?;
+        if outcome == stats::Outcome::Won {
+            won += 1;
+        }
+        let session = client.session_stats();
+        println!(
+            "{}: {:?}; score: {}; moves: {}; deals: {}",
+            client.seed(),
+            outcome,
+            client.score(),
+            session.moves,
+            session.deals
+        );
+    }
+    println!();
+    println!(
+        "strategy: {}; games: {}; won: {}; win rate: {:.1}%",
+        config.strategy.name(),
+        games,
+        won,
+        100.0 * won as f64 / games.max(1) as f64
+    );
     Ok(())
 }
@@ -0,0 +1,119 @@
+use anyhow::Result;
+use rustyline::error::ReadlineError;
+use rustyline::Editor;
+
+use super::commands::{self, Command};
+use super::config::Config;
+use super::display;
+use super::stats;
+
+// Turn is how a player's turn ended
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Turn {
+    Next,
+    Done(stats::Outcome),
+    Quit,
+}
+
+// run races the players through the same deal, taking turns at the prompt
+pub fn run(config: &Config, names: &[String]) -> Result<()> {
+    let seed = match &config.seed {
+        Some(seed) => seed.clone(),
+        None => game::seed::from_random().to_string(),
+    };
+    let mut race = client::race::Race::new(config.layout.clone(), config.undo_mode, &seed, names)?;
+    let mut outcomes = vec![stats::Outcome::Abandoned; names.len()];
+    let mut rl = Editor::<()>::new();
+
+    println!(
+        "race of {} on {}: {}",
+        names.join(", "),
+        config.layout.name,
+        race.seed()
+    );
+    let mut turn = Some(0);
+    while let Some(i) = turn {
+        race.start_turn(i);
+        let ended = play_turn(&mut race, i, &mut rl, config)?;
+        race.end_turn(i);
+        match ended {
+            Turn::Next => {}
+            Turn::Done(outcome) => {
+                outcomes[i] = outcome;
+                race.racer_mut(i).finished = true;
+            }
+            Turn::Quit => break,
+        }
+        display::scoreboard(&race.scoreboard());
+        turn = race.next_turn(i);
+    }
+
+    println!("final ranking");
+    display::scoreboard(&race.scoreboard());
+    for (racer, outcome) in race.racers().iter().zip(outcomes) {
+        if racer.client.checkpoints().len() > 1 || outcome != stats::Outcome::Abandoned {
+            stats::append(
+                stats::STATS_FILE,
+                &stats::Record::new(&racer.client, outcome, &racer.name),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn play_turn(
+    race: &mut client::race::Race,
+    i: usize,
+    rl: &mut Editor<()>,
+    config: &Config,
+) -> Result<Turn> {
+    let name = race.racers()[i].name.clone();
+    println!();
+    println!("{}'s turn", name);
+    loop {
+        let client = &mut race.racer_mut(i).client;
+        display::local_game(client, &config.style);
+        if client.is_won() {
+            println!("{} won: score {}", name, client.score());
+            return Ok(Turn::Done(stats::Outcome::Won));
+        }
+        if commands::is_lost(client)? {
+            println!("{} has no moves left", name);
+            return Ok(Turn::Done(stats::Outcome::Lost));
+        }
+        display::possible_moves(client)?;
+
+        let line = match rl.readline(&format!("{}>> ", name)) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => return Ok(Turn::Quit),
+            Err(err) => return Err(err.into()),
+        };
+        rl.add_history_entry(line.as_str());
+        match line.trim() {
+            "" => continue,
+            "next" => return Ok(Turn::Next),
+            "resign" => return Ok(Turn::Done(stats::Outcome::Abandoned)),
+            "scores" => {
+                display::scoreboard(&race.scoreboard());
+                continue;
+            }
+            _ => {}
+        }
+        let client = &mut race.racer_mut(i).client;
+        match line.parse::<Command>() {
+            Err(e) => println!("{}", e),
+            Ok(Command::Help) => display::race_help(),
+            Ok(Command::Quit) => return Ok(Turn::Quit),
+            Ok(Command::Status) => println!("{:?}", client.session_stats()),
+            Ok(Command::Moves) | Ok(Command::Board) => {}
+            Ok(Command::Checkpoints) | Ok(Command::Auto) | Ok(Command::Json) => {
+                println!("{} is not available in a race", line.trim())
+            }
+            Ok(command) => {
+                if let Err(e) = commands::play(client, command) {
+                    println!("{}", e);
+                }
+            }
+        }
+    }
+}
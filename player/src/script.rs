@@ -0,0 +1,238 @@
+// script runs player commands without a prompt, from a file or stdin,
+// writing one machine-readable line for each command:
+//
+//   ok <command>
+//   error <message>
+//   move <n> orig=<col> count=<cards> dest=<col> used=<bool>
+//   status seed=<seed> dealt=<n> remaining=<n> runs=<n> ...
+//   column <n>: ## ## QH JH
+//   json {"seed":...}
+//   result won|lost|unfinished score=<n>
+//
+// Blank lines and lines starting with # are skipped, except for the
+// headers solve writes, "# seed: <seed>", "# variant: <name>" and
+// "# empty columns: <rule>", which must match the game being played.
+// The script stops at the first error or at quit. Scripted games are not
+// recorded in the stats.
+
+use anyhow::{bail, Result};
+use std::io::BufRead;
+use std::{thread, time};
+
+use super::commands::{self, Command};
+use super::display;
+
+// exit codes
+pub const WON: i32 = 0;
+pub const LOST: i32 = 1;
+pub const ERROR: i32 = 2;
+pub const UNFINISHED: i32 = 3;
+
+// run returns the exit code for the player process; a failure to read
+// the script or to report on the game is an error, never a lost game
+pub fn run<R: BufRead>(client: &mut client::Client, input: R) -> i32 {
+    match play(client, input) {
+        Ok(code) => code,
+        Err(e) => {
+            println!("error {}", e);
+            ERROR
+        }
+    }
+}
+
+fn play<R: BufRead>(client: &mut client::Client, input: R) -> Result<i32> {
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.starts_with('#') {
+            check_header(client, line)?;
+            continue;
+        }
+        if line.is_empty() {
+            continue;
+        }
+        let command = match line.parse::<Command>() {
+            Ok(command) => command,
+            Err(e) => {
+                println!("error {}", e);
+                return Ok(ERROR);
+            }
+        };
+        match command {
+            Command::Quit => break,
+            Command::Moves => moves(client)?,
+            Command::Status => status(client),
+            Command::Board => board(client),
+            Command::Json => println!("json {}", client.to_json()?),
+            Command::Help | Command::Checkpoints | Command::Auto => {
+                println!("error {} is not available in scripts", line);
+                return Ok(ERROR);
+            }
+            _ => match commands::play(client, command) {
+                Ok(()) => println!("ok {}", line),
+                Err(e) => {
+                    println!("error {}", e);
+                    return Ok(ERROR);
+                }
+            },
+        }
+    }
+
+    let (result, code) = if client.is_won() {
+        ("won", WON)
+    } else if commands::is_lost(client)? {
+        ("lost", LOST)
+    } else {
+        ("unfinished", UNFINISHED)
+    };
+    println!("result {} score={}", result, client.score());
+    Ok(code)
+}
+
+// replay plays the commands of a script back for a person to watch,
+// showing the board before each one
+pub fn replay<R: BufRead>(
+    client: &mut client::Client,
+    input: R,
+    style: &cards::render::Style,
+    delay: u64,
+) -> Result<()> {
+    for (i, line) in input.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.starts_with('#') {
+            if let Err(e) = check_header(client, line) {
+                bail!("line {}: {}", i + 1, e);
+            }
+            continue;
+        }
+        if line.is_empty() {
+            continue;
+        }
+        display::local_game(client, style);
+        println!();
+        println!(">> {}", line);
+        match line.parse::<Command>()? {
+            Command::Quit => break,
+            Command::Moves | Command::Status | Command::Board | Command::Json => {}
+            Command::Help | Command::Checkpoints | Command::Auto => {
+                bail!("line {}: {} is not available in scripts", i + 1, line)
+            }
+            command => {
+                if let Err(e) = commands::play(client, command) {
+                    bail!("line {}: {}", i + 1, e);
+                }
+            }
+        }
+        thread::sleep(time::Duration::from_secs(delay));
+    }
+
+    display::local_game(client, style);
+    if client.is_won() {
+        println!("game won: score {}", client.score());
+    }
+    Ok(())
+}
+
+// check_header refuses a script written for another deal or other rules
+fn check_header(client: &client::Client, line: &str) -> Result<()> {
+    let (name, expected, actual, flag) = match line.trim_start_matches('#').split_once(':') {
+        Some((name, value)) => {
+            let name = name.trim();
+            let actual = match name {
+                "seed" => client.seed(),
+                "variant" => client.layout().name.clone(),
+                "empty columns" => client.layout().empty_columns.to_string(),
+                _ => return Ok(()),
+            };
+            (name, value.trim(), actual, name.replace(' ', "-"))
+        }
+        None => return Ok(()),
+    };
+    if expected != actual {
+        bail!(
+            "the script is for {} {}, not {}: play it with --{} {}",
+            name,
+            expected,
+            actual,
+            flag,
+            expected
+        );
+    }
+    Ok(())
+}
+
+fn moves(client: &client::Client) -> Result<()> {
+    for (i, m) in client.possible_moves()?.iter().enumerate() {
+        println!(
+            "move {} orig={} count={} dest={} used={}",
+            i + 1,
+            m.mv.orig_col,
+            m.mv.count,
+            m.mv.dest_col,
+            m.is_used
+        );
+    }
+    Ok(())
+}
+
+fn status(client: &client::Client) {
+    let session = client.session_stats();
+    println!(
+        "status seed={} dealt={} remaining={} runs={} moves={} undos={} deals={} score={} won={} digest={}",
+        client.seed(),
+        client.cards_dealt(),
+        client.total_cards() - client.cards_dealt(),
+        client.completed_runs(),
+        session.moves,
+        session.undos,
+        session.deals,
+        client.score(),
+        client.is_won(),
+        client.digest()
+    );
+}
+
+// board writes the columns as the player sees them, ## for a hidden card
+fn board(client: &client::Client) {
+    for (i, column) in client.local.iter().enumerate() {
+        let cards: Vec<String> = column
+            .iter()
+            .map(|card| match card {
+                Some(card) => format!("{}{}", card.rank.to_string().trim(), card.suit),
+                None => "##".to_string(),
+            })
+            .collect();
+        println!("column {}: {}", i, cards.join(" "));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headers() {
+        let mut client = client::Client::from_hex("v1-0123456789abcdef0123456789abcdef").unwrap();
+        let script = "\
+# seed: v1-0123456789abcdef0123456789abcdef
+# variant: spider
+# empty columns: strict
+# positions searched: 10
+deal
+";
+        assert_eq!(run(&mut client, script.as_bytes()), UNFINISHED);
+
+        for header in [
+            "# seed: v1-00000000000000000000000000000001",
+            "# variant: spider-1-suit",
+            "# empty columns: fill",
+        ] {
+            let mut client =
+                client::Client::from_hex("v1-0123456789abcdef0123456789abcdef").unwrap();
+            let script = format!("{}\ndeal\n", header);
+            assert_eq!(run(&mut client, script.as_bytes()), ERROR, "{}", header);
+            assert_eq!(client.cards_dealt(), 54, "{}", header);
+        }
+    }
+}
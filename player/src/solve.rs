@@ -0,0 +1,76 @@
+// solve searches a deal for a winning line and writes it as a script,
+// with the deal in header comments, that replay can play back
+
+use anyhow::Result;
+use std::fmt::Write;
+
+use super::config::Config;
+
+pub fn run(config: &Config, limit: usize) -> Result<()> {
+    let seed = match &config.seed {
+        Some(seed) => seed.parse()?,
+        None => game::seed::from_random(),
+    };
+    let game = game::Game::from_layout_seed(config.layout.clone(), seed)?;
+    print!("{}", script(&game, limit)?);
+    Ok(())
+}
+
+// script is the solver's report on the game as a script
+pub fn script(game: &game::Game, limit: usize) -> Result<String> {
+    let report = game::solver::solve(&game::packed::Packed::from_game(game)?, game.rules(), limit);
+
+    let mut text = String::new();
+    if let Some(seed) = game.seed() {
+        writeln!(text, "# seed: {}", seed)?;
+    }
+    writeln!(text, "# variant: {}", game.layout().name)?;
+    writeln!(text, "# empty columns: {}", game.layout().empty_columns)?;
+    writeln!(text, "# positions searched: {}", report.nodes)?;
+    match report.verdict {
+        game::solver::Verdict::Solved(line) => {
+            writeln!(text, "# solved in {} steps", line.len())?;
+            for step in line {
+                match step {
+                    game::solver::Step::Deal => writeln!(text, "deal")?,
+                    game::solver::Step::Move(m) => {
+                        writeln!(text, "move {} {} {}", m.orig_col, m.count, m.dest_col)?
+                    }
+                }
+            }
+        }
+        game::solver::Verdict::Unsolvable => writeln!(text, "# no winning line")?,
+        game::solver::Verdict::Unknown => writeln!(text, "# gave up after {} positions", limit)?,
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script;
+
+    #[test]
+    fn solutions_replay() {
+        let layout = game::layout::Layout::spider_one_suit();
+        let mut solved = 0;
+        for i in 4..=6u8 {
+            let hex_seed = format!("v1-{:032x}", i);
+            let game =
+                game::Game::from_layout_seed(layout.clone(), hex_seed.parse().unwrap()).unwrap();
+            let text = script(&game, 20_000).unwrap();
+            if !text.contains("# solved in") {
+                continue;
+            }
+            solved += 1;
+            let mut client = client::Client::from_layout(layout.clone(), Some(&hex_seed)).unwrap();
+            assert_eq!(
+                script::run(&mut client, text.as_bytes()),
+                script::WON,
+                "{}",
+                hex_seed
+            );
+        }
+        assert!(solved > 0);
+    }
+}
@@ -0,0 +1,387 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+// one JSON record per line, appended as each game finishes
+pub const STATS_FILE: &str = "stats.jsonl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Won,
+    Lost,
+    Abandoned,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub seed: String,
+    pub variant: String,
+    // the house rule the game was dealt by; records from before
+    // there was a choice were strict
+    #[serde(default)]
+    pub empty_columns: game::rules::EmptyColumns,
+    // the undo mode the game was played in; unlimited before there were modes
+    #[serde(default)]
+    pub undo: game::undo::UndoMode,
+    pub outcome: Outcome,
+    pub score: i64,
+    pub moves: usize,
+    pub seconds: u64,
+    pub date: String,
+    // the human's name, or the name of the strategy that played
+    pub player: String,
+}
+
+impl Record {
+    pub fn new(client: &client::Client, outcome: Outcome, player: &str) -> Record {
+        let session = client.session_stats();
+        Record {
+            seed: client.seed(),
+            variant: client.layout().name.clone(),
+            empty_columns: client.layout().empty_columns,
+            undo: client.undo_mode(),
+            outcome,
+            score: client.score(),
+            moves: session.moves,
+            seconds: session.elapsed.as_secs(),
+            date: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            player: player.to_string(),
+        }
+    }
+}
+
+// human_name is the login name of whoever is at the keyboard
+pub fn human_name() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "player".to_string())
+}
+
+pub fn append<P: AsRef<Path>>(path: P, record: &Record) -> Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+    Ok(())
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<Record>> {
+    if !path.as_ref().exists() {
+        return Ok(Vec::new());
+    }
+    let mut records: Vec<Record> = Vec::new();
+    for line in BufReader::new(std::fs::File::open(path)?).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            records.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(records)
+}
+
+// Streak counts consecutive wins
+#[derive(Debug, Default)]
+struct Streak {
+    current: usize,
+    best: usize,
+}
+
+impl Streak {
+    fn add(&mut self, record: &Record) {
+        if record.outcome == Outcome::Won {
+            self.current += 1;
+            self.best = self.best.max(self.current);
+        } else {
+            self.current = 0;
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Summary {
+    games: usize,
+    won: usize,
+    best_score: Option<i64>,
+    fastest_win: Option<u64>,
+}
+
+impl Summary {
+    fn add(&mut self, record: &Record) {
+        self.games += 1;
+        if record.outcome == Outcome::Won {
+            self.won += 1;
+            self.fastest_win = Some(
+                self.fastest_win
+                    .map_or(record.seconds, |s| s.min(record.seconds)),
+            );
+        }
+        self.best_score = Some(
+            self.best_score
+                .map_or(record.score, |s| s.max(record.score)),
+        );
+    }
+
+    fn win_rate(&self) -> f64 {
+        if self.games == 0 {
+            0.0
+        } else {
+            100.0 * self.won as f64 / self.games as f64
+        }
+    }
+
+    fn line(&self) -> String {
+        format!(
+            "games: {}; won: {}; win rate: {:.1}%; best score: {}; fastest win: {}",
+            self.games,
+            self.won,
+            self.win_rate(),
+            self.best_score.map_or("-".to_string(), |s| s.to_string()),
+            self.fastest_win.map_or("-".to_string(), minutes),
+        )
+    }
+}
+
+pub fn minutes(seconds: u64) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+// Totals summarises the records overall, by variant and by player
+#[derive(Debug, Default)]
+struct Totals<'a> {
+    all: Summary,
+    by_variant: BTreeMap<&'a str, Summary>,
+    by_player: BTreeMap<&'a str, Summary>,
+    // a streak is one player's run of wins at one variant
+    streaks: BTreeMap<(&'a str, &'a str), Streak>,
+}
+
+fn totals(records: &[Record]) -> Totals<'_> {
+    let mut totals = Totals::default();
+    for record in records {
+        totals.all.add(record);
+        totals
+            .by_variant
+            .entry(&record.variant)
+            .or_default()
+            .add(record);
+        totals
+            .by_player
+            .entry(&record.player)
+            .or_default()
+            .add(record);
+        totals
+            .streaks
+            .entry((&record.player, &record.variant))
+            .or_default()
+            .add(record);
+    }
+    totals
+}
+
+pub fn show<P: AsRef<Path>>(path: P) -> Result<()> {
+    let records = load(path)?;
+    if records.is_empty() {
+        println!("no games recorded");
+        return Ok(());
+    }
+
+    let totals = totals(&records);
+    println!("{}", totals.all.line());
+    println!();
+    println!("by variant");
+    for (variant, summary) in &totals.by_variant {
+        println!("{:>12}: {}", variant, summary.line());
+    }
+    println!();
+    println!("by player");
+    for (player, summary) in &totals.by_player {
+        println!("{:>12}: {}", player, summary.line());
+    }
+    println!();
+    println!("winning streaks");
+    for ((player, variant), streak) in &totals.streaks {
+        println!(
+            "{:>12}: {}: current: {}; best: {}",
+            player, variant, streak.current, streak.best
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(player: &str, variant: &str, outcome: Outcome, score: i64, seconds: u64) -> Record {
+        Record {
+            seed: "v1-0123456789abcdef0123456789abcdef".to_string(),
+            variant: variant.to_string(),
+            empty_columns: game::rules::EmptyColumns::Strict,
+            undo: game::undo::UndoMode::Unlimited,
+            outcome,
+            score,
+            moves: 0,
+            seconds,
+            date: "2024-05-01 12:00:00".to_string(),
+            player: player.to_string(),
+        }
+    }
+
+    struct TestData<'a> {
+        name: String,
+        summary: Option<&'a Summary>,
+        expected_games: usize,
+        expected_win_rate: f64,
+        expected_best_score: Option<i64>,
+        expected_fastest_win: Option<u64>,
+    }
+
+    struct StreakTestData<'a> {
+        name: String,
+        streak: Option<&'a Streak>,
+        expected_current: usize,
+        expected_best: usize,
+    }
+
+    #[test]
+    fn test_totals() {
+        use Outcome::*;
+
+        let path = std::env::temp_dir().join(format!("stats-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        for r in [
+            record("ann", "spider", Won, 700, 300),
+            record("ann", "spiderette", Won, 500, 100),
+            record("bob", "spider", Lost, 50, 600),
+            record("ann", "spider", Won, 650, 200),
+            record("bob", "spiderette", Won, 900, 500),
+            record("ann", "spider", Lost, 100, 900),
+            record("bob", "spider", Abandoned, 20, 30),
+            record("bob", "spiderette", Won, 600, 250),
+            record("ann", "spider", Won, 800, 400),
+        ] {
+            append(&path, &r).unwrap();
+        }
+        let records = load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(records.len(), 9);
+        let totals = totals(&records);
+
+        let test_items = vec![
+            TestData {
+                name: "all".to_string(),
+                summary: Some(&totals.all),
+                expected_games: 9,
+                expected_win_rate: 600.0 / 9.0,
+                expected_best_score: Some(900),
+                expected_fastest_win: Some(100),
+            },
+            TestData {
+                name: "spider".to_string(),
+                summary: totals.by_variant.get("spider"),
+                expected_games: 6,
+                expected_win_rate: 50.0,
+                expected_best_score: Some(800),
+                expected_fastest_win: Some(200),
+            },
+            TestData {
+                name: "spiderette".to_string(),
+                summary: totals.by_variant.get("spiderette"),
+                expected_games: 3,
+                expected_win_rate: 100.0,
+                expected_best_score: Some(900),
+                expected_fastest_win: Some(100),
+            },
+            TestData {
+                name: "ann".to_string(),
+                summary: totals.by_player.get("ann"),
+                expected_games: 5,
+                expected_win_rate: 80.0,
+                expected_best_score: Some(800),
+                expected_fastest_win: Some(100),
+            },
+            TestData {
+                name: "bob".to_string(),
+                summary: totals.by_player.get("bob"),
+                expected_games: 4,
+                expected_win_rate: 50.0,
+                expected_best_score: Some(900),
+                expected_fastest_win: Some(250),
+            },
+        ];
+        for test_item in test_items {
+            let summary = test_item.summary.expect(&test_item.name);
+            assert_eq!(
+                summary.games, test_item.expected_games,
+                "{}",
+                test_item.name
+            );
+            assert!(
+                (summary.win_rate() - test_item.expected_win_rate).abs() < 1e-9,
+                "{}",
+                test_item.name
+            );
+            assert_eq!(
+                summary.best_score, test_item.expected_best_score,
+                "{}",
+                test_item.name
+            );
+            assert_eq!(
+                summary.fastest_win, test_item.expected_fastest_win,
+                "{}",
+                test_item.name
+            );
+        }
+        assert_eq!(totals.by_variant.len(), 2);
+        assert_eq!(totals.by_player.len(), 2);
+        assert_eq!(
+            totals.all.line(),
+            "games: 9; won: 6; win rate: 66.7%; best score: 900; fastest win: 1:40"
+        );
+        assert_eq!(
+            Summary::default().line(),
+            "games: 0; won: 0; win rate: 0.0%; best score: -; fastest win: -"
+        );
+
+        let streak = |player, variant| totals.streaks.get(&(player, variant));
+        let test_items = vec![
+            StreakTestData {
+                name: "ann at spider".to_string(),
+                streak: streak("ann", "spider"),
+                expected_current: 1,
+                expected_best: 2,
+            },
+            StreakTestData {
+                name: "ann at spiderette".to_string(),
+                streak: streak("ann", "spiderette"),
+                expected_current: 1,
+                expected_best: 1,
+            },
+            StreakTestData {
+                name: "bob at spider".to_string(),
+                streak: streak("bob", "spider"),
+                expected_current: 0,
+                expected_best: 0,
+            },
+            StreakTestData {
+                name: "bob at spiderette".to_string(),
+                streak: streak("bob", "spiderette"),
+                expected_current: 2,
+                expected_best: 2,
+            },
+        ];
+        assert_eq!(totals.streaks.len(), test_items.len());
+        for test_item in test_items {
+            let streak = test_item.streak.expect(&test_item.name);
+            assert_eq!(
+                (streak.current, streak.best),
+                (test_item.expected_current, test_item.expected_best),
+                "{}",
+                test_item.name
+            );
+        }
+    }
+}
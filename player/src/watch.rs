@@ -0,0 +1,60 @@
+use anyhow::{anyhow, Result};
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::{thread, time};
+
+use super::config::Config;
+use super::display;
+
+// stream_path is the file a game broadcasts to, named for its game id
+pub fn stream_path(game_id: &str) -> PathBuf {
+    PathBuf::from(format!("{}.watch.jsonl", game_id))
+}
+
+// broadcast starts streaming the client's game if --broadcast names an id
+pub fn broadcast(config: &Config, client: &mut client::Client) -> Result<()> {
+    if let Some(game_id) = &config.broadcast {
+        let path = stream_path(game_id);
+        let file = std::fs::File::create(&path)
+            .map_err(|e| anyhow!("unable to broadcast to {}: {}", path.display(), e))?;
+        client.broadcast(Box::new(file))?;
+    }
+    Ok(())
+}
+
+// run shows the game broadcast under the id, or on stdin for -, following
+// the stream as it grows until the game ends
+pub fn run(game_id: &str, style: &cards::render::Style) -> Result<()> {
+    let mut input: Box<dyn BufRead> = if game_id == "-" {
+        Box::new(BufReader::new(std::io::stdin()))
+    } else {
+        let path = stream_path(game_id);
+        let file = std::fs::File::open(&path)
+            .map_err(|e| anyhow!("no game {} to watch in {}: {}", game_id, path.display(), e))?;
+        Box::new(BufReader::new(file))
+    };
+
+    let mut spectator = client::spectator::Spectator::default();
+    let mut line = String::new();
+    while !spectator.over {
+        let read = input.read_line(&mut line)?;
+        if read == 0 || !line.ends_with('\n') {
+            // a pipe at its end has closed; a file may still grow,
+            // and finish the line
+            if read == 0 && game_id == "-" {
+                break;
+            }
+            thread::sleep(time::Duration::from_millis(250));
+            continue;
+        }
+        spectator.read_line(&line)?;
+        line.clear();
+        display::spectator(&spectator, style);
+    }
+
+    if spectator.over {
+        println!();
+        println!("game over: score {}", spectator.status.score);
+    }
+    Ok(())
+}